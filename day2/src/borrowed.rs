@@ -0,0 +1,164 @@
+// Zero-copy version of the password parser.
+//
+// `Password` here borrows the password straight out of the input instead of copying it into a
+// `String`, and `crate::split_line` splits lines byte by byte without allocating, so walking a whole
+// database doesn't touch the heap.
+
+use crate::Policy;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Password<'a> {
+    pub password: &'a str,
+    pub letter: char,
+    pub range: (i32, i32),
+}
+
+impl<'a> Password<'a> {
+    #[inline(always)]
+    pub fn is_in_range(&self, count: i32) -> bool {
+        self.range.0 <= count && count <= self.range.1
+    }
+
+    #[inline(always)]
+    pub fn count_letter(&self) -> i32 {
+        crate::scan::count_letter(self.password, self.letter)
+    }
+
+    #[inline(always)]
+    pub fn letter_at(&self, pos: i32) -> bool {
+        pos >= 1 && self.password.chars().nth(pos as usize - 1) == Some(self.letter)
+    }
+
+    // Same as `Policy::is_valid`, without needing an owned `crate::Password`.
+    #[inline(always)]
+    pub fn is_valid(&self, policy: Policy) -> bool {
+        match policy {
+            Policy::Count => self.is_in_range(self.count_letter()),
+            Policy::Positional => self.letter_at(self.range.0) ^ self.letter_at(self.range.1),
+        }
+    }
+}
+
+impl<'a> From<Password<'a>> for crate::Password {
+    fn from(pword: Password<'a>) -> Self {
+        crate::Password {
+            password: pword.password.to_string(),
+            letter: pword.letter,
+            range: pword.range,
+        }
+    }
+}
+
+// Parses a single `lo-hi letter: password` line, or returns `None` if it's malformed.
+pub fn parse_line(line: &str) -> Option<Password<'_>> {
+    let fields = crate::split_line(line).ok()?;
+    Some(Password {
+        password: fields.password,
+        letter: fields.letter.chars().next()?,
+        range: fields.range,
+    })
+}
+
+// Lazily parses every line of `input`, panicking on malformed lines like `crate::parse_passwords`.
+pub fn parse_passwords(input: &str) -> impl Iterator<Item = Password<'_>> {
+    input.lines().map(|line| {
+        parse_line(line).unwrap_or_else(|| panic!("Malformed password line {:?}", line))
+    })
+}
+
+pub fn part1(input: &str) -> i32 {
+    parse_passwords(input).fold(0, |acc, pword| {
+        if pword.is_valid(Policy::Count) {
+            acc + 1
+        } else {
+            acc
+        }
+    })
+}
+
+// Unlike `crate::part2`, positions past the end of the password just don't match instead of panicking.
+pub fn part2(input: &str) -> i32 {
+    parse_passwords(input).fold(0, |acc, pword| {
+        if pword.is_valid(Policy::Positional) {
+            acc + 1
+        } else {
+            acc
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_file;
+    use std::path::Path;
+    use test::Bencher;
+
+    // Builds a deterministic database of `lines` entries, roughly 40 bytes per line.
+    fn generate_database(lines: usize) -> String {
+        let mut next = crate::tests::lcg();
+
+        let mut input = String::with_capacity(lines * 40);
+        for _ in 0..lines {
+            let len = 8 + next() % 24;
+            let lo = 1 + next() % len;
+            let hi = lo + next() % (len - lo + 1);
+            let letter = (b'a' + (next() % 26) as u8) as char;
+            input.push_str(&format!("{}-{} {}: ", lo, hi, letter));
+            for _ in 0..len {
+                input.push((b'a' + (next() % 26) as u8) as char);
+            }
+            input.push('\n');
+        }
+        input
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("13-15 x: rgnqfdxsvlplxjx"),
+            Some(Password {
+                password: "rgnqfdxsvlplxjx",
+                letter: 'x',
+                range: (13, 15),
+            })
+        );
+        assert_eq!(parse_line("13-15 x rgnqfdxsvlplxjx"), None);
+        assert_eq!(parse_line("13 x: rgnqfdxsvlplxjx"), None);
+        assert_eq!(parse_line("1-3 a:"), None);
+    }
+
+    #[test]
+    fn test_matches_owned_parser() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let owned = crate::parse_passwords(&input);
+        let borrowed: Vec<crate::Password> = parse_passwords(&input).map(Into::into).collect();
+        assert_eq!(owned, borrowed);
+        assert_eq!(part1(&input), crate::part1(&input));
+        assert_eq!(part2(&input), crate::part2(&input));
+    }
+
+    #[bench]
+    fn bench_part1_owned(b: &mut Bencher) {
+        let input = generate_database(100_000);
+        b.iter(|| crate::part1(&input))
+    }
+
+    #[bench]
+    fn bench_part1_borrowed(b: &mut Bencher) {
+        let input = generate_database(100_000);
+        b.iter(|| part1(&input))
+    }
+
+    #[bench]
+    fn bench_part2_owned(b: &mut Bencher) {
+        let input = generate_database(100_000);
+        b.iter(|| crate::part2(&input))
+    }
+
+    #[bench]
+    fn bench_part2_borrowed(b: &mut Bencher) {
+        let input = generate_database(100_000);
+        b.iter(|| part2(&input))
+    }
+}
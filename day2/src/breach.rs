@@ -0,0 +1,149 @@
+// Offline check of passwords against a local breach list or dictionary.
+//
+// Every entry is stored as its SHA-1 hash in one sorted array, 20 bytes per entry with no
+// per-entry allocations, and looked up with a binary search. Lists can be plain words, one per
+// line, or HIBP-style SHA-1 hex hashes optionally followed by `:count`.
+
+use crate::Password;
+use sha1::{Digest, Sha1};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+type Hash = [u8; 20];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Plain,
+    Sha1,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Wordlist {
+    hashes: Vec<Hash>,
+}
+
+fn sha1(word: &str) -> Hash {
+    Sha1::digest(word.as_bytes()).into()
+}
+
+fn parse_hex_hash(hex: &str) -> Option<Hash> {
+    // `from_str_radix` would take a `+` in front, so check the digits first.
+    if hex.len() != 40 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut hash = [0; 20];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(hash)
+}
+
+impl Wordlist {
+    pub fn load(path: &Path, format: Format) -> io::Result<Wordlist> {
+        Wordlist::from_reader(BufReader::new(File::open(path)?), format)
+    }
+
+    // Blank lines are skipped, a malformed hash is an `InvalidData` error with its line number.
+    pub fn from_reader<R: BufRead>(reader: R, format: Format) -> io::Result<Wordlist> {
+        let mut hashes = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                continue;
+            }
+
+            hashes.push(match format {
+                Format::Plain => sha1(line),
+                Format::Sha1 => {
+                    let hex = line.split(':').next().unwrap_or("").trim();
+                    parse_hex_hash(hex).ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("invalid SHA-1 hash on line {}: {:?}", i + 1, line),
+                        )
+                    })?
+                }
+            });
+        }
+
+        hashes.sort_unstable();
+        hashes.dedup();
+        hashes.shrink_to_fit();
+        Ok(Wordlist { hashes })
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        self.hashes.binary_search(&sha1(password)).is_ok()
+    }
+}
+
+// Returns the 0-based index and entry of every password that's in `wordlist`.
+pub fn find_breached<'a>(
+    passwords: &'a [Password],
+    wordlist: &Wordlist,
+) -> Vec<(usize, &'a Password)> {
+    passwords
+        .iter()
+        .enumerate()
+        .filter(|(_, pword)| wordlist.contains(&pword.password))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_passwords;
+
+    #[test]
+    fn test_plain_wordlist() {
+        let wordlist = Wordlist::from_reader(
+            "password\r\nabcde\n\nabcde\nhunter2\n".as_bytes(),
+            Format::Plain,
+        )
+        .unwrap();
+        assert_eq!(wordlist.len(), 3);
+        assert!(wordlist.contains("abcde"));
+        assert!(wordlist.contains("hunter2"));
+        assert!(!wordlist.contains("ABCDE"));
+        assert!(!wordlist.contains(""));
+    }
+
+    #[test]
+    fn test_sha1_wordlist() {
+        // SHA-1 of "password" as HIBP lists it, and of "abcde" in lowercase without a count.
+        let list = "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:3861493\n\
+                    03de6c570bfe24bfc328ccd7ca46b76eadaf4334\n";
+        let wordlist = Wordlist::from_reader(list.as_bytes(), Format::Sha1).unwrap();
+        assert!(wordlist.contains("password"));
+        assert!(wordlist.contains("abcde"));
+        assert!(!wordlist.contains("hunter2"));
+
+        let err = Wordlist::from_reader("5BAA61E4:12\n".as_bytes(), Format::Sha1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 1"));
+
+        let plus = "+BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8\n";
+        assert!(Wordlist::from_reader(plus.as_bytes(), Format::Sha1).is_err());
+    }
+
+    #[test]
+    fn test_find_breached() {
+        let passwords = parse_passwords("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc");
+        let wordlist =
+            Wordlist::from_reader("ccccccccc\nabcde\n".as_bytes(), Format::Plain).unwrap();
+        assert_eq!(
+            find_breached(&passwords, &wordlist),
+            vec![(0, &passwords[0]), (2, &passwords[2])]
+        );
+    }
+}
@@ -0,0 +1,385 @@
+// Policy config files, so which rules get counted doesn't have to be hardcoded.
+//
+// A config is TOML, or JSON if the file ends in `.json`, declaring named policies and optionally
+// which of them to `run` (all of them, in order, if it's left out). See policies.toml for the
+// puzzle's two policies written out this way.
+//
+//   run = ["part1", "part2"]
+//
+//   [[policy]]
+//   name = "part1"
+//   kind = "count"            # letter count in range
+//   range = "inclusive"       # or "exclusive", default inclusive
+//   case_sensitive = true     # default true, false is the same as matching = "case-fold"
+//   matching = "exact"        # or "case-insensitive" or "case-fold", see matching.rs
+//
+//   [[policy]]
+//   name = "part2"
+//   kind = "positional"       # letter at exactly one of the positions
+//   index_base = 1            # 0 or 1, default 1
+//   case_sensitive = true     # and `matching`, same as for count
+//
+//   [[policy]]
+//   name = "strong"
+//   kind = "strength"         # see strength.rs
+//   min_bits = 40.0
+//
+//   [[policy]]
+//   name = "no-digits"
+//   kind = "rule"             # see rules.rs
+//   rule = "NOT class(digit)"
+
+use crate::matching::{MatchMode, Matcher};
+use crate::rules::{Rule, RuleError};
+use crate::strength::StrengthPolicy;
+use crate::Password;
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RangeBounds {
+    #[default]
+    Inclusive,
+    Exclusive,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_index_base() -> i32 {
+    1
+}
+
+// An explicit `matching` wins over `case_sensitive`.
+fn match_mode(case_sensitive: bool, matching: Option<MatchMode>) -> MatchMode {
+    matching.unwrap_or(if case_sensitive {
+        MatchMode::Exact
+    } else {
+        MatchMode::CaseFold
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum PolicyKind {
+    Count {
+        #[serde(default)]
+        range: RangeBounds,
+        #[serde(default = "default_true")]
+        case_sensitive: bool,
+        matching: Option<MatchMode>,
+    },
+    Positional {
+        #[serde(default = "default_index_base")]
+        index_base: i32,
+        #[serde(default = "default_true")]
+        case_sensitive: bool,
+        matching: Option<MatchMode>,
+    },
+    Strength {
+        min_bits: f64,
+    },
+    Rule {
+        rule: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PolicyConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: PolicyKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub run: Option<Vec<String>>,
+    #[serde(rename = "policy", default)]
+    pub policies: Vec<PolicyConfig>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    UnknownPolicy(String),
+    InvalidIndexBase { policy: String, index_base: i32 },
+    InvalidRule { policy: String, err: RuleError },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "couldn't read config: {}", err),
+            ConfigError::Toml(err) => write!(f, "invalid TOML config: {}", err),
+            ConfigError::Json(err) => write!(f, "invalid JSON config: {}", err),
+            ConfigError::UnknownPolicy(name) => {
+                write!(f, "`run` lists {:?}, which isn't a declared policy", name)
+            }
+            ConfigError::InvalidIndexBase { policy, index_base } => write!(
+                f,
+                "policy {:?} has index_base {}, expected 0 or 1",
+                policy, index_base
+            ),
+            ConfigError::InvalidRule { policy, err } => {
+                write!(f, "policy {:?} has an invalid rule: {}", policy, err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+// A policy from the config, checked and ready to run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Check {
+    Count { exclusive: bool, mode: MatchMode },
+    Positional { index_base: i32, mode: MatchMode },
+    Strength(StrengthPolicy),
+    Rule(Rule),
+}
+
+impl Check {
+    pub fn is_valid(&self, pword: &Password) -> bool {
+        match self {
+            // Letters are matched the same way as `--match`, see matching.rs.
+            Check::Count { exclusive, mode } => {
+                let count = Matcher::Letter(pword.letter, *mode).count(&pword.password);
+                if *exclusive {
+                    pword.range.0 < count && count < pword.range.1
+                } else {
+                    pword.is_in_range(count)
+                }
+            }
+            Check::Positional { index_base, mode } => {
+                let matcher = Matcher::Letter(pword.letter, *mode);
+                let letter_at = |pos: i32| {
+                    pos.checked_sub(*index_base)
+                        .is_some_and(|index| matcher.matches_at(&pword.password, index))
+                };
+                letter_at(pword.range.0) ^ letter_at(pword.range.1)
+            }
+            Check::Strength(policy) => policy.is_valid(pword),
+            Check::Rule(rule) => rule.matches(pword),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedCheck {
+    pub name: String,
+    pub check: Check,
+}
+
+impl Config {
+    pub fn from_toml(input: &str) -> Result<Config, ConfigError> {
+        toml::from_str(input).map_err(ConfigError::Toml)
+    }
+
+    pub fn from_json(input: &str) -> Result<Config, ConfigError> {
+        serde_json::from_str(input).map_err(ConfigError::Json)
+    }
+
+    // Picks the format from the extension, `.json` for JSON and anything else for TOML.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let input = fs::read_to_string(path)?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Config::from_json(&input)
+        } else {
+            Config::from_toml(&input)
+        }
+    }
+
+    // Checks every policy and returns the ones listed in `run`, in that order.
+    pub fn checks(&self) -> Result<Vec<NamedCheck>, ConfigError> {
+        let checks = self
+            .policies
+            .iter()
+            .map(|policy| {
+                let check = match &policy.kind {
+                    PolicyKind::Count {
+                        range,
+                        case_sensitive,
+                        matching,
+                    } => Check::Count {
+                        exclusive: *range == RangeBounds::Exclusive,
+                        mode: match_mode(*case_sensitive, *matching),
+                    },
+                    PolicyKind::Positional {
+                        index_base,
+                        case_sensitive,
+                        matching,
+                    } => {
+                        if *index_base != 0 && *index_base != 1 {
+                            return Err(ConfigError::InvalidIndexBase {
+                                policy: policy.name.clone(),
+                                index_base: *index_base,
+                            });
+                        }
+                        Check::Positional {
+                            index_base: *index_base,
+                            mode: match_mode(*case_sensitive, *matching),
+                        }
+                    }
+                    PolicyKind::Strength { min_bits } => Check::Strength(StrengthPolicy {
+                        min_bits: *min_bits,
+                    }),
+                    PolicyKind::Rule { rule } => {
+                        Check::Rule(Rule::parse(rule).map_err(|err| ConfigError::InvalidRule {
+                            policy: policy.name.clone(),
+                            err,
+                        })?)
+                    }
+                };
+                Ok(NamedCheck {
+                    name: policy.name.clone(),
+                    check,
+                })
+            })
+            .collect::<Result<Vec<NamedCheck>, ConfigError>>()?;
+
+        match &self.run {
+            None => Ok(checks),
+            Some(run) => run
+                .iter()
+                .map(|name| {
+                    checks
+                        .iter()
+                        .find(|check| check.name == *name)
+                        .cloned()
+                        .ok_or_else(|| ConfigError::UnknownPolicy(name.clone()))
+                })
+                .collect(),
+        }
+    }
+}
+
+// Number of passwords passing each check, in the same order as `checks`.
+pub fn count_valid(passwords: &[Password], checks: &[NamedCheck]) -> Vec<(String, i32)> {
+    checks
+        .iter()
+        .map(|named| {
+            let count = passwords.iter().fold(0, |acc, pword| {
+                if named.check.is_valid(pword) {
+                    acc + 1
+                } else {
+                    acc
+                }
+            });
+            (named.name.clone(), count)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_file, matching, parse_passwords, part1, part2, Policy};
+
+    #[test]
+    fn test_example_config_matches_parts() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let config = Config::load(Path::new("policies.toml")).unwrap();
+        let counts = count_valid(&parse_passwords(&input), &config.checks().unwrap());
+        assert_eq!(
+            counts,
+            vec![
+                ("part1".to_string(), part1(&input)),
+                ("part2".to_string(), part2(&input)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_options() {
+        let config = Config::from_json(
+            r#"{
+                "run": ["zero-based", "exclusive", "any-case", "rule", "folded"],
+                "policy": [
+                    { "name": "exclusive", "kind": "count", "range": "exclusive" },
+                    { "name": "any-case", "kind": "count", "case_sensitive": false },
+                    { "name": "zero-based", "kind": "positional", "index_base": 0 },
+                    { "name": "rule", "kind": "rule", "rule": "len(5, 5)" },
+                    { "name": "folded", "kind": "positional", "matching": "case-fold" },
+                    { "name": "unused", "kind": "strength", "min_bits": 10 }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let passwords = parse_passwords("1-3 a: abcde\n1-3 b: cdefg\n0-2 A: aaAaa\n1-4 c: cccc");
+        let counts: Vec<i32> = count_valid(&passwords, &config.checks().unwrap())
+            .into_iter()
+            .map(|(_, count)| count)
+            .collect();
+        // zero-based: "aaAaa" has A at index 2, and "cccc" has c at 1 but nothing at 4.
+        // exclusive: only "aaAaa" with a single A is strictly inside its range.
+        // any-case: "aaAaa" now has five A's, too many for 0-2.
+        // folded: "aaAaa" now has A at 2 but not at 0, which is before the start.
+        assert_eq!(counts, vec![2, 1, 2, 3, 2]);
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let err = Config::from_toml("run = [\"missing\"]").unwrap().checks();
+        assert!(matches!(err, Err(ConfigError::UnknownPolicy(name)) if name == "missing"));
+
+        let config =
+            Config::from_toml("[[policy]]\nname = \"bad\"\nkind = \"positional\"\nindex_base = 2")
+                .unwrap();
+        assert!(matches!(
+            config.checks(),
+            Err(ConfigError::InvalidIndexBase { index_base: 2, .. })
+        ));
+
+        let config =
+            Config::from_toml("[[policy]]\nname = \"bad\"\nkind = \"rule\"\nrule = \"pos(\"")
+                .unwrap();
+        assert!(matches!(
+            config.checks(),
+            Err(ConfigError::InvalidRule { .. })
+        ));
+
+        assert!(matches!(
+            Config::from_toml("[[policy]]\nname = \"bad\"\nkind = \"length\""),
+            Err(ConfigError::Toml(_))
+        ));
+    }
+
+    #[test]
+    fn test_matches_like_match_mode() {
+        let input = "1-2 \u{df}: \u{1e9e}x\u{df}\n2-3 s: \u{17f}S\n1-1 \u{3c3}: \u{3c2}";
+        let config = Config::from_toml(
+            "[[policy]]\nname = \"count\"\nkind = \"count\"\nmatching = \"case-fold\"\n\
+             [[policy]]\nname = \"positional\"\nkind = \"positional\"\nmatching = \"case-fold\"",
+        )
+        .unwrap();
+        let counts = count_valid(&parse_passwords(input), &config.checks().unwrap());
+        assert_eq!(
+            counts,
+            vec![
+                (
+                    "count".to_string(),
+                    matching::count_valid(input, Policy::Count, MatchMode::CaseFold)
+                ),
+                (
+                    "positional".to_string(),
+                    matching::count_valid(input, Policy::Positional, MatchMode::CaseFold)
+                ),
+            ]
+        );
+        assert_eq!(counts[0].1, 3);
+    }
+}
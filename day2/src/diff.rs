@@ -0,0 +1,189 @@
+// Differences between two password dumps, say last month's and this month's.
+//
+// Entries are matched up by password. A password that's in both dumps but with a different
+// policy line is changed, and may have become valid or invalid under either policy. Repeated
+// passwords are matched up in the order they appear, so two copies in the old dump and three in
+// the new one is one added entry.
+
+use crate::{Password, Policy};
+use std::collections::HashMap;
+
+pub const POLICIES: [Policy; 2] = [Policy::Count, Policy::Positional];
+
+// An entry with its 0-based index in the dump it came from.
+pub type Entry<'a> = (usize, &'a Password);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matched<'a> {
+    pub old: Entry<'a>,
+    pub new: Entry<'a>,
+}
+
+impl Matched<'_> {
+    pub fn is_changed(&self) -> bool {
+        self.old.1 != self.new.1
+    }
+
+    // Validity under `policy` before and after.
+    pub fn validity(&self, policy: Policy) -> (bool, bool) {
+        (policy.is_valid(self.old.1), policy.is_valid(self.new.1))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Transitions {
+    pub stayed_valid: usize,
+    pub became_valid: usize,
+    pub became_invalid: usize,
+    pub stayed_invalid: usize,
+    // Valid entries that are only in the new or old dump.
+    pub added_valid: usize,
+    pub removed_valid: usize,
+}
+
+impl Transitions {
+    pub fn valid_before(&self) -> usize {
+        self.stayed_valid + self.became_invalid + self.removed_valid
+    }
+
+    pub fn valid_after(&self) -> usize {
+        self.stayed_valid + self.became_valid + self.added_valid
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diff<'a> {
+    pub added: Vec<Entry<'a>>,
+    pub removed: Vec<Entry<'a>>,
+    // Every entry in both dumps, changed or not, in new dump order.
+    pub matched: Vec<Matched<'a>>,
+}
+
+impl<'a> Diff<'a> {
+    pub fn new(old: &'a [Password], new: &'a [Password]) -> Diff<'a> {
+        let mut unmatched: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, pword) in old.iter().enumerate().rev() {
+            unmatched.entry(&pword.password).or_default().push(i);
+        }
+
+        let mut added = Vec::new();
+        let mut matched = Vec::new();
+        for (i, pword) in new.iter().enumerate() {
+            match unmatched
+                .get_mut(pword.password.as_str())
+                .and_then(Vec::pop)
+            {
+                Some(j) => matched.push(Matched {
+                    old: (j, &old[j]),
+                    new: (i, pword),
+                }),
+                None => added.push((i, pword)),
+            }
+        }
+
+        let mut removed: Vec<Entry> = unmatched
+            .into_values()
+            .flatten()
+            .map(|j| (j, &old[j]))
+            .collect();
+        removed.sort_by_key(|&(j, _)| j);
+
+        Diff {
+            added,
+            removed,
+            matched,
+        }
+    }
+
+    pub fn changed(&self) -> impl Iterator<Item = &Matched<'a>> {
+        self.matched.iter().filter(|matched| matched.is_changed())
+    }
+
+    pub fn transitions(&self, policy: Policy) -> Transitions {
+        let mut transitions = Transitions::default();
+        for matched in &self.matched {
+            match matched.validity(policy) {
+                (true, true) => transitions.stayed_valid += 1,
+                (false, true) => transitions.became_valid += 1,
+                (true, false) => transitions.became_invalid += 1,
+                (false, false) => transitions.stayed_invalid += 1,
+            }
+        }
+        let valid = |entries: &[Entry]| {
+            entries
+                .iter()
+                .filter(|(_, pword)| policy.is_valid(pword))
+                .count()
+        };
+        transitions.added_valid = valid(&self.added);
+        transitions.removed_valid = valid(&self.removed);
+        transitions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_file, parse_passwords, part1, part2};
+    use std::path::Path;
+
+    #[test]
+    fn test_diff_entries() {
+        let old = parse_passwords("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-1 z: zz");
+        let new =
+            parse_passwords("1-3 a: abcde\n3-4 c: ccccccccc\n1-3 x: xyz\n1-1 z: zz\n1-2 z: zz");
+        let diff = Diff::new(&old, &new);
+
+        assert_eq!(diff.added, vec![(2, &new[2]), (4, &new[4])]);
+        assert_eq!(diff.removed, vec![(1, &old[1])]);
+        let changed: Vec<(usize, usize)> = diff
+            .changed()
+            .map(|matched| (matched.old.0, matched.new.0))
+            .collect();
+        assert_eq!(changed, vec![(2, 1)]);
+    }
+
+    #[test]
+    fn test_transitions() {
+        let old = parse_passwords("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc");
+        let new = parse_passwords("1-3 a: abcde\n1-3 c: ccccccccc\n1-3 x: xyz");
+        let diff = Diff::new(&old, &new);
+
+        let count = diff.transitions(Policy::Count);
+        // "ccccccccc" had 9 c's in 2-9, but not in 1-3.
+        assert_eq!(
+            count,
+            Transitions {
+                stayed_valid: 1,
+                became_invalid: 1,
+                added_valid: 1,
+                ..Transitions::default()
+            }
+        );
+        assert_eq!(count.valid_before(), 2);
+        assert_eq!(count.valid_after(), 2);
+
+        let positional = diff.transitions(Policy::Positional);
+        // c is at both positions before and after, so it stays invalid.
+        assert_eq!(positional.stayed_invalid, 1);
+        assert_eq!(positional.valid_before(), 1);
+        assert_eq!(positional.valid_after(), 2);
+    }
+
+    #[test]
+    fn test_same_dump() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let passwords = parse_passwords(&input);
+        let diff = Diff::new(&passwords, &passwords);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.changed().count(), 0);
+        assert_eq!(
+            diff.transitions(Policy::Count).valid_after(),
+            part1(&input) as usize
+        );
+        assert_eq!(
+            diff.transitions(Policy::Positional).valid_after(),
+            part2(&input) as usize
+        );
+    }
+}
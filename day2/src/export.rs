@@ -0,0 +1,84 @@
+// JSON and CSV import/export of password databases, behind the `serde` feature.
+//
+// JSON keeps the `Password` layout as is, CSV flattens the range into `lo` and `hi` columns.
+
+use crate::Password;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvRecord {
+    lo: i32,
+    hi: i32,
+    letter: char,
+    password: String,
+}
+
+pub fn to_json(passwords: &[Password]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(passwords)
+}
+
+pub fn from_json(input: &str) -> serde_json::Result<Vec<Password>> {
+    serde_json::from_str(input)
+}
+
+pub fn write_csv<W: Write>(passwords: &[Password], writer: W) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for pword in passwords {
+        writer.serialize(CsvRecord {
+            lo: pword.range.0,
+            hi: pword.range.1,
+            letter: pword.letter,
+            password: pword.password.clone(),
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn read_csv<R: Read>(reader: R) -> csv::Result<Vec<Password>> {
+    csv::Reader::from_reader(reader)
+        .deserialize()
+        .map(|record| {
+            let record: CsvRecord = record?;
+            Ok(Password {
+                password: record.password,
+                letter: record.letter,
+                range: (record.lo, record.hi),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::password_strategy;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_csv_layout() {
+        let passwords = crate::parse_passwords("1-3 a: abcde\n2-9 c: ccccccccc");
+        let mut csv = Vec::new();
+        write_csv(&passwords, &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "lo,hi,letter,password\n1,3,a,abcde\n2,9,c,ccccccccc\n"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_json_round_trip(passwords in prop::collection::vec(password_strategy(), 0..20)) {
+            let json = to_json(&passwords).unwrap();
+            prop_assert_eq!(from_json(&json).unwrap(), passwords);
+        }
+
+        #[test]
+        fn test_csv_round_trip(passwords in prop::collection::vec(password_strategy(), 0..20)) {
+            let mut csv = Vec::new();
+            write_csv(&passwords, &mut csv).unwrap();
+            prop_assert_eq!(read_csv(csv.as_slice()).unwrap(), passwords);
+        }
+    }
+}
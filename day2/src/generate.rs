@@ -0,0 +1,144 @@
+// Random password databases with a known number of valid entries, for fuzzing the policies.
+//
+// Passwords are lowercase ascii, and positional ranges always stay inside the password, so
+// generated entries are fine to feed to `part2`.
+
+use crate::{Password, Policy};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const MAX_LEN: usize = 20;
+
+fn random_letter<R: Rng>(rng: &mut R) -> char {
+    (b'a' + rng.gen_range(0..26)) as char
+}
+
+fn random_letter_except<R: Rng>(rng: &mut R, letter: char) -> char {
+    loop {
+        let c = random_letter(rng);
+        if c != letter {
+            return c;
+        }
+    }
+}
+
+// Generates a password that is guaranteed to pass `policy` if `valid` is true, and to fail it
+// otherwise.
+pub fn generate_password<R: Rng>(rng: &mut R, policy: Policy, valid: bool) -> Password {
+    let letter = random_letter(rng);
+    let len = rng.gen_range(2..=MAX_LEN);
+    let lo = rng.gen_range(1..len);
+    let hi = rng.gen_range(lo + 1..=len);
+
+    let password = match policy {
+        Policy::Count => {
+            let count = if valid {
+                rng.gen_range(lo..=hi)
+            } else if hi == len || rng.gen() {
+                rng.gen_range(0..lo)
+            } else {
+                rng.gen_range(hi + 1..=len)
+            };
+
+            let mut chars: Vec<char> = (0..len)
+                .map(|i| {
+                    if i < count {
+                        letter
+                    } else {
+                        random_letter_except(rng, letter)
+                    }
+                })
+                .collect();
+            chars.shuffle(rng);
+            chars.into_iter().collect()
+        }
+        Policy::Positional => {
+            let (at_lo, at_hi) = match (valid, rng.gen::<bool>()) {
+                (true, first) => (first, !first),
+                (false, both) => (both, both),
+            };
+
+            (1..=len)
+                .map(|pos| match pos {
+                    pos if pos == lo && at_lo => letter,
+                    pos if pos == hi && at_hi => letter,
+                    pos if pos == lo || pos == hi => random_letter_except(rng, letter),
+                    _ => random_letter(rng),
+                })
+                .collect()
+        }
+    };
+
+    Password {
+        password,
+        letter,
+        range: (lo as i32, hi as i32),
+    }
+}
+
+// Generates `valid` passing and `invalid` failing passwords for `policy`, in a random order.
+pub fn generate_database<R: Rng>(
+    rng: &mut R,
+    policy: Policy,
+    valid: usize,
+    invalid: usize,
+) -> Vec<Password> {
+    let mut passwords: Vec<Password> = (0..valid + invalid)
+        .map(|i| generate_password(rng, policy, i < valid))
+        .collect();
+    passwords.shuffle(rng);
+    passwords
+}
+
+// Writes `passwords` out in the puzzle's input format.
+pub fn to_input(passwords: &[Password]) -> String {
+    passwords
+        .iter()
+        .map(|pword| pword.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{part1, part2};
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_generate_password() {
+        let mut rng = StdRng::seed_from_u64(2020);
+        for &policy in [Policy::Count, Policy::Positional].iter() {
+            for _ in 0..1000 {
+                assert!(policy.is_valid(&generate_password(&mut rng, policy, true)));
+                assert!(!policy.is_valid(&generate_password(&mut rng, policy, false)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_database_is_seeded() {
+        let database =
+            |seed| generate_database(&mut StdRng::seed_from_u64(seed), Policy::Count, 5, 5);
+        assert_eq!(database(1), database(1));
+        assert_ne!(database(1), database(2));
+    }
+
+    proptest! {
+        #[test]
+        fn test_part1_counts_planted_passwords(seed in any::<u64>(), valid in 0..200_usize, invalid in 0..200_usize) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let input = to_input(&generate_database(&mut rng, Policy::Count, valid, invalid));
+            prop_assert_eq!(part1(&input), valid as i32);
+        }
+
+        #[test]
+        fn test_part2_counts_planted_passwords(seed in any::<u64>(), valid in 0..200_usize, invalid in 0..200_usize) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let input = to_input(&generate_database(&mut rng, Policy::Positional, valid, invalid));
+            prop_assert_eq!(part2(&input), valid as i32);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+// Unicode-aware policies that work on extended grapheme clusters instead of `char`s.
+//
+// `Password` splits on codepoints, so "é" written as e + combining accent is two positions, an
+// emoji flag is two, and a multi-codepoint letter gets cut down to its first codepoint. Here the
+// password and letter are NFC normalized first, and then positions and letters are whole
+// grapheme clusters, which is what someone reading the password would count.
+
+use crate::Policy;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextMode {
+    Chars,
+    Graphemes,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphemePassword {
+    pub password: String,
+    // A single grapheme cluster.
+    pub letter: String,
+    pub range: (i32, i32),
+}
+
+impl GraphemePassword {
+    pub fn count_letter(&self) -> i32 {
+        self.password
+            .graphemes(true)
+            .fold(0, |acc, g| if g == self.letter { acc + 1 } else { acc })
+    }
+
+    // Whether the letter is the grapheme at the 1-based position `pos`.
+    pub fn letter_at(&self, pos: i32) -> bool {
+        pos >= 1 && self.password.graphemes(true).nth(pos as usize - 1) == Some(&self.letter)
+    }
+
+    pub fn is_valid(&self, policy: Policy) -> bool {
+        match policy {
+            Policy::Count => {
+                let count = self.count_letter();
+                self.range.0 <= count && count <= self.range.1
+            }
+            Policy::Positional => self.letter_at(self.range.0) ^ self.letter_at(self.range.1),
+        }
+    }
+}
+
+// Parses a `lo-hi letter: password` line, or returns `None` if it's malformed or the letter isn't
+// exactly one grapheme cluster.
+pub fn parse_line(line: &str) -> Option<GraphemePassword> {
+    let fields = crate::split_line(line).ok()?;
+    let letter: String = fields.letter.nfc().collect();
+    if letter.graphemes(true).count() != 1 {
+        return None;
+    }
+
+    Some(GraphemePassword {
+        password: fields.password.nfc().collect(),
+        letter,
+        range: fields.range,
+    })
+}
+
+pub fn parse_passwords(input: &str) -> Vec<GraphemePassword> {
+    input
+        .lines()
+        .map(|line| {
+            parse_line(line).unwrap_or_else(|| panic!("Malformed password line {:?}", line))
+        })
+        .collect()
+}
+
+// Get the number of passwords passing `policy`. `TextMode::Chars` is the same as `part1`/`part2`.
+pub fn count_valid(input: &str, policy: Policy, mode: TextMode) -> i32 {
+    match (mode, policy) {
+        (TextMode::Chars, Policy::Count) => crate::part1(input),
+        (TextMode::Chars, Policy::Positional) => crate::part2(input),
+        (TextMode::Graphemes, _) => parse_passwords(input).iter().fold(0, |acc, pword| {
+            if pword.is_valid(policy) {
+                acc + 1
+            } else {
+                acc
+            }
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_file, part1, part2};
+    use std::path::Path;
+
+    // "é" as e + combining acute accent.
+    const DECOMPOSED_E: &str = "e\u{301}";
+    const FLAG: &str = "\u{1F1EB}\u{1F1F7}";
+    const FAMILY: &str = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+
+    #[test]
+    fn test_parse_multi_codepoint_letter() {
+        let pword = parse_line(&format!("1-2 {}: caf{}", DECOMPOSED_E, DECOMPOSED_E)).unwrap();
+        // Both sides get normalized to the precomposed U+00E9.
+        assert_eq!(pword.letter, "\u{e9}");
+        assert_eq!(pword.password, "caf\u{e9}");
+
+        assert_eq!(parse_line("1-2 ab: abc"), None);
+        assert_eq!(parse_line("1-2 a: "), None);
+    }
+
+    #[test]
+    fn test_accented_positions() {
+        // The precomposed letter still matches the decomposed password.
+        let input = format!("4-5 \u{e9}: caf{}s", DECOMPOSED_E);
+        assert_eq!(
+            count_valid(&input, Policy::Positional, TextMode::Graphemes),
+            1
+        );
+        assert_eq!(count_valid(&input, Policy::Count, TextMode::Graphemes), 0);
+        // Counting chars, position 4 is a bare "e" and nothing matches.
+        assert_eq!(count_valid(&input, Policy::Positional, TextMode::Chars), 0);
+
+        let input = format!("1-1 \u{e9}: {}t{}", DECOMPOSED_E, DECOMPOSED_E);
+        assert_eq!(count_valid(&input, Policy::Count, TextMode::Graphemes), 0);
+        let input = format!("2-2 \u{e9}: {}t{}", DECOMPOSED_E, DECOMPOSED_E);
+        assert_eq!(count_valid(&input, Policy::Count, TextMode::Graphemes), 1);
+    }
+
+    #[test]
+    fn test_emoji_positions() {
+        let pword = parse_line(&format!("1-3 {}: {}a{}", FLAG, FLAG, FLAG)).unwrap();
+        assert_eq!(pword.count_letter(), 2);
+        assert!(pword.letter_at(1));
+        assert!(!pword.letter_at(2));
+        assert!(pword.letter_at(3));
+        assert!(!pword.is_valid(Policy::Positional));
+
+        let pword = parse_line(&format!("2-4 {}: x{}yz", FAMILY, FAMILY)).unwrap();
+        assert!(pword.is_valid(Policy::Positional));
+        assert!(!pword.is_valid(Policy::Count));
+        assert!(!pword.letter_at(4));
+    }
+
+    #[test]
+    fn test_ascii_input_agrees_with_parts() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        assert_eq!(
+            count_valid(&input, Policy::Count, TextMode::Graphemes),
+            part1(&input)
+        );
+        assert_eq!(
+            count_valid(&input, Policy::Positional, TextMode::Graphemes),
+            part2(&input)
+        );
+    }
+}
@@ -0,0 +1,513 @@
+#![feature(test)]
+extern crate test;
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+pub mod borrowed;
+pub mod breach;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod diff;
+#[cfg(feature = "serde")]
+pub mod export;
+pub mod generate;
+pub mod graphemes;
+pub mod lint;
+pub mod matching;
+pub mod repair;
+pub mod rules;
+pub mod scan;
+pub mod stats;
+pub mod stream;
+pub mod strength;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Password {
+    pub password: String,
+    pub letter: char,
+    pub range: (i32, i32),
+}
+
+impl Password {
+    #[inline(always)]
+    pub fn is_in_range(&self, count: i32) -> bool {
+        if self.range.0 <= count && count <= self.range.1 {
+            return true;
+        }
+        false
+    }
+
+    // Number of times `letter` appears in the password.
+    #[inline(always)]
+    pub fn count_letter(&self) -> i32 {
+        scan::count_letter(&self.password, self.letter)
+    }
+
+    // Whether `letter` is at the 1-based position `pos`. Positions outside the password never match.
+    #[inline(always)]
+    pub fn letter_at(&self, pos: i32) -> bool {
+        pos >= 1 && self.password.chars().nth(pos as usize - 1) == Some(self.letter)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Policy {
+    // The letter has to appear between lo and hi times (part 1).
+    Count,
+    // The letter has to be at exactly one of the 1-based positions lo and hi (part 2).
+    Positional,
+}
+
+impl Policy {
+    pub fn is_valid(&self, pword: &Password) -> bool {
+        match self {
+            Policy::Count => pword.is_in_range(pword.count_letter()),
+            Policy::Positional => pword.letter_at(pword.range.0) ^ pword.letter_at(pword.range.1),
+        }
+    }
+}
+
+// Writes the password back out in the same `lo-hi letter: password` format it was parsed from.
+impl fmt::Display for Password {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} {}: {}",
+            self.range.0, self.range.1, self.letter, self.password
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsePasswordError {
+    MissingRange,
+    InvalidRange(String),
+    MissingLetter,
+    InvalidLetter(String),
+    MissingPassword,
+}
+
+impl fmt::Display for ParsePasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParsePasswordError::MissingRange => write!(f, "missing `lo-hi` range"),
+            ParsePasswordError::InvalidRange(range) => write!(f, "invalid range {:?}", range),
+            ParsePasswordError::MissingLetter => write!(f, "missing `letter:`"),
+            ParsePasswordError::InvalidLetter(letter) => {
+                write!(f, "expected a single letter, found {:?}", letter)
+            }
+            ParsePasswordError::MissingPassword => write!(f, "missing password"),
+        }
+    }
+}
+
+impl std::error::Error for ParsePasswordError {}
+
+// Stricter than `parse_passwords`: the line has to be exactly what `Display` writes, so that
+// `pword.to_string().parse()` always gives back `pword`.
+impl FromStr for Password {
+    type Err = ParsePasswordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (range, rest) = s.split_once(' ').ok_or(ParsePasswordError::MissingRange)?;
+        let invalid_range = || ParsePasswordError::InvalidRange(range.to_string());
+        // Skip the first character when looking for the dash so a negative lo still parses.
+        let dash = range
+            .get(1..)
+            .and_then(|r| r.find('-'))
+            .ok_or_else(invalid_range)?
+            + 1;
+        let range = (
+            range[..dash].parse().map_err(|_| invalid_range())?,
+            range[dash + 1..].parse().map_err(|_| invalid_range())?,
+        );
+
+        let (letter, password) = rest
+            .split_once(": ")
+            .ok_or(ParsePasswordError::MissingLetter)?;
+        let mut chars = letter.chars();
+        let letter = match (chars.next(), chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(ParsePasswordError::InvalidLetter(letter.to_string())),
+        };
+        if password.is_empty() {
+            return Err(ParsePasswordError::MissingPassword);
+        }
+
+        Ok(Password {
+            password: password.to_string(),
+            letter,
+            range,
+        })
+    }
+}
+
+pub fn load_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut input = String::new();
+    file.read_to_string(&mut input)?;
+    Ok(input)
+}
+
+// The fields of a `lo-hi letter: password` line, before the letter is interpreted. Every line
+// parser apart from the strict `FromStr` starts from this, so each counting mode accepts the same
+// lines and only differs in what it makes of the letter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fields<'a> {
+    pub range: (i32, i32),
+    pub letter: &'a str,
+    pub password: &'a str,
+}
+
+// Parses an optionally signed run of ascii digits starting at `start`, returning the number and
+// the index just past it.
+#[inline(always)]
+fn parse_number(bytes: &[u8], start: usize) -> Option<(i32, usize)> {
+    let (negative, digits) = match bytes.get(start) {
+        Some(b'-') => (true, start + 1),
+        Some(b'+') => (false, start + 1),
+        _ => (false, start),
+    };
+    let mut end = digits;
+    let mut number: i32 = 0;
+    while let Some(&b) = bytes.get(end) {
+        if !b.is_ascii_digit() {
+            break;
+        }
+        let digit = (b - b'0') as i32;
+        number = number.checked_mul(10)?;
+        number = if negative {
+            number.checked_sub(digit)?
+        } else {
+            number.checked_add(digit)?
+        };
+        end += 1;
+    }
+
+    if end == digits {
+        return None;
+    }
+    Some((number, end))
+}
+
+// Length in bytes of the UTF-8 sequence starting with `lead`.
+#[inline(always)]
+fn utf8_len(lead: u8) -> usize {
+    match lead {
+        0x00..=0x7f => 1,
+        0xf0..=0xff => 4,
+        0xe0..=0xef => 3,
+        _ => 2,
+    }
+}
+
+// Splits a line into its fields. Any amount of ascii whitespace can go between them, and the space
+// after the colon is optional. The letter runs up to the first colon after its first character, or
+// up to a colon ending the word, so `:` itself and classes like `[:upper:]` work as letters.
+//
+// The line is scanned byte by byte and only ever sliced, so this doesn't allocate unless it fails.
+// Everything it splits on is ascii, which never shows up inside a multi-byte character, so the
+// slices always land on char boundaries.
+pub fn split_line(line: &str) -> Result<Fields<'_>, ParsePasswordError> {
+    let bytes = line.as_bytes();
+    let is_space = |b: &u8| b.is_ascii_whitespace();
+    let skip_spaces = |from: usize, to: usize| {
+        from + bytes[from..to]
+            .iter()
+            .position(|b| !is_space(b))
+            .unwrap_or(to - from)
+    };
+    let start = skip_spaces(0, bytes.len());
+    let end = bytes
+        .iter()
+        .rposition(|b| !is_space(b))
+        .map_or(start, |i| i + 1);
+
+    let range_end = start
+        + bytes[start..end]
+            .iter()
+            .position(is_space)
+            .ok_or(ParsePasswordError::MissingRange)?;
+    let invalid_range = || ParsePasswordError::InvalidRange(line[start..range_end].to_string());
+    let (lo, i) = parse_number(bytes, start).ok_or_else(invalid_range)?;
+    if bytes.get(i) != Some(&b'-') {
+        return Err(invalid_range());
+    }
+    let (hi, i) = parse_number(bytes, i + 1).ok_or_else(invalid_range)?;
+    if i != range_end {
+        return Err(invalid_range());
+    }
+
+    let word_start = skip_spaces(range_end, end);
+    let word_end = word_start
+        + bytes[word_start..end]
+            .iter()
+            .position(is_space)
+            .unwrap_or(end - word_start);
+    let word = &bytes[word_start..word_end];
+    let colon = if word.len() > 1 && word.last() == Some(&b':') {
+        word_end - 1
+    } else {
+        let first = word.first().map_or(0, |&lead| utf8_len(lead));
+        word_start
+            + first
+            + word[first..]
+                .iter()
+                .position(|&b| b == b':')
+                .ok_or(ParsePasswordError::MissingLetter)?
+    };
+    let password_start = skip_spaces(colon + 1, end);
+    if password_start == end {
+        return Err(ParsePasswordError::MissingPassword);
+    }
+
+    Ok(Fields {
+        range: (lo, hi),
+        letter: &line[word_start..colon],
+        password: &line[password_start..end],
+    })
+}
+
+// Parses every line of `input`, panicking on malformed lines. Only the first char of the letter is
+// kept, see graphemes.rs for letters that take more than one.
+pub fn parse_passwords(input: &str) -> Vec<Password> {
+    input
+        .lines()
+        .map(|line| {
+            let fields = split_line(line)
+                .unwrap_or_else(|err| panic!("Malformed password line {:?}: {}", line, err));
+            Password {
+                password: fields.password.to_string(),
+                letter: fields.letter.chars().next().unwrap(),
+                range: fields.range,
+            }
+        })
+        .collect()
+}
+
+// Get the number of valid passwords.
+pub fn part1(input: &str) -> i32 {
+    let mut valid_passwords = 0;
+    let passwords = parse_passwords(input);
+
+    for pword in passwords.iter() {
+        if pword.is_in_range(pword.count_letter()) {
+            valid_passwords += 1;
+        }
+    }
+
+    valid_passwords
+}
+
+pub fn part2(input: &str) -> i32 {
+    let mut valid_passwords = 0;
+    let passwords = parse_passwords(input);
+
+    for pword in passwords.iter() {
+        let chars: Vec<char> = pword.password.chars().collect();
+        let matches: (bool, bool) = (
+            chars[pword.range.0 as usize - 1] == pword.letter,
+            chars[pword.range.1 as usize - 1] == pword.letter,
+        );
+
+        // ^ is XOR, meaning only true if exactly one is true, otherwise false.
+        if matches.0 ^ matches.1 {
+            valid_passwords += 1;
+        }
+    }
+
+    valid_passwords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use matching::MatchMode;
+    use proptest::prelude::*;
+
+    pub(crate) fn password_strategy() -> impl Strategy<Value = Password> {
+        ("[^\\r\\n]{1,20}", any::<char>(), any::<(i32, i32)>()).prop_map(
+            |(password, letter, range)| Password {
+                password,
+                letter,
+                range,
+            },
+        )
+    }
+
+    // Numerical Recipes LCG with a fixed seed, for test and bench data that's the same every run.
+    pub(crate) fn lcg() -> impl FnMut() -> usize {
+        let mut state: u64 = 0x2020_0002;
+        move || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1);
+            (state >> 33) as usize
+        }
+    }
+
+    #[test]
+    fn test_parse_single_password() {
+        let input = "13-15 x: rgnqfdxsvlplxjx\n2-3 g: sxpw".to_string();
+        let password = parse_passwords(&input);
+        assert_eq!(
+            password[0],
+            Password {
+                password: "rgnqfdxsvlplxjx".to_string(),
+                letter: 'x',
+                range: (13, 15),
+            }
+        );
+        assert_eq!(
+            password[1],
+            Password {
+                password: "sxpw".to_string(),
+                letter: 'g',
+                range: (2, 3),
+            }
+        );
+    }
+
+    #[test]
+    fn test_split_line() {
+        let fields = |range, letter, password| {
+            Ok(Fields {
+                range,
+                letter,
+                password,
+            })
+        };
+        assert_eq!(split_line("1-3 a: abc"), fields((1, 3), "a", "abc"));
+        assert_eq!(split_line("1-3 a:abc"), fields((1, 3), "a", "abc"));
+        assert_eq!(split_line("1-3  a:  abc\r"), fields((1, 3), "a", "abc"));
+        assert_eq!(split_line("1-2 :: x"), fields((1, 2), ":", "x"));
+        assert_eq!(split_line("1-2 ::x"), fields((1, 2), ":", "x"));
+        assert_eq!(
+            split_line("1-2 [:upper:]: Ab"),
+            fields((1, 2), "[:upper:]", "Ab")
+        );
+        assert_eq!(split_line("-1-2 a: b: c"), fields((-1, 2), "a", "b: c"));
+        assert_eq!(
+            split_line("1--2 \u{e9}:\u{e9}"),
+            fields((1, -2), "\u{e9}", "\u{e9}")
+        );
+        assert_eq!(
+            split_line("-2147483648-2147483647 a: abc"),
+            fields((i32::MIN, i32::MAX), "a", "abc")
+        );
+
+        assert_eq!(split_line("1-3"), Err(ParsePasswordError::MissingRange));
+        for range in &["1:3", "1-", "-1", "1-3x", "--1-2", "1-2147483648"] {
+            assert_eq!(
+                split_line(&format!("{} a: abc", range)),
+                Err(ParsePasswordError::InvalidRange(range.to_string()))
+            );
+        }
+        assert_eq!(
+            split_line("1-3 a abc"),
+            Err(ParsePasswordError::MissingLetter)
+        );
+        assert_eq!(
+            split_line("1-3 : abc"),
+            Err(ParsePasswordError::MissingLetter)
+        );
+        assert_eq!(
+            split_line("1-3 a:"),
+            Err(ParsePasswordError::MissingPassword)
+        );
+    }
+
+    // Lines split the same way in every counting mode, spacing and odd letters and all.
+    const SPLIT_LINES: [&str; 4] = ["1-3 a:abc", "1-3  a:  abc", "1-2 :: x:", "1-3 a: abc\r"];
+
+    #[test]
+    fn test_parsers_share_split_line() {
+        for line in &SPLIT_LINES {
+            let pword = &parse_passwords(line)[0];
+            let borrowed = borrowed::parse_line(line).unwrap();
+            assert_eq!(&crate::Password::from(borrowed), pword);
+
+            let grapheme = graphemes::parse_line(line).unwrap();
+            assert_eq!(grapheme.letter, pword.letter.to_string());
+            assert_eq!(grapheme.password, pword.password);
+            assert_eq!(grapheme.range, pword.range);
+
+            let matched = matching::parse_line(line, MatchMode::Exact).unwrap();
+            assert_eq!(
+                matched.matcher,
+                matching::Matcher::Letter(pword.letter, MatchMode::Exact)
+            );
+            assert_eq!(matched.password, pword.password);
+            assert_eq!(matched.range, pword.range);
+        }
+        let input = SPLIT_LINES.join("\n");
+        assert_eq!(
+            matching::count_valid(&input, Policy::Count, MatchMode::Exact),
+            part1(&input)
+        );
+        assert_eq!(
+            graphemes::parse_line("1-2 :: x:").unwrap().count_letter(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_display_password() {
+        let pword = Password {
+            password: "abcde".to_string(),
+            letter: 'a',
+            range: (1, 3),
+        };
+        assert_eq!(pword.to_string(), "1-3 a: abcde");
+        assert_eq!("1-3 a: abcde".parse(), Ok(pword));
+    }
+
+    #[test]
+    fn test_parse_password_errors() {
+        assert_eq!(
+            "1-3".parse::<Password>(),
+            Err(ParsePasswordError::MissingRange)
+        );
+        assert_eq!(
+            "1:3 a: abcde".parse::<Password>(),
+            Err(ParsePasswordError::InvalidRange("1:3".to_string()))
+        );
+        assert_eq!(
+            "1-x a: abcde".parse::<Password>(),
+            Err(ParsePasswordError::InvalidRange("1-x".to_string()))
+        );
+        assert_eq!(
+            "1-3 a abcde".parse::<Password>(),
+            Err(ParsePasswordError::MissingLetter)
+        );
+        assert_eq!(
+            "1-3 ab: abcde".parse::<Password>(),
+            Err(ParsePasswordError::InvalidLetter("ab".to_string()))
+        );
+        assert_eq!(
+            "1-3 a: ".parse::<Password>(),
+            Err(ParsePasswordError::MissingPassword)
+        );
+    }
+
+    #[test]
+    fn test_input_round_trips() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        for (line, pword) in input.lines().zip(parse_passwords(&input)) {
+            assert_eq!(pword.to_string(), line);
+            assert_eq!(line.parse(), Ok(pword));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_password_round_trip(pword in password_strategy()) {
+            prop_assert_eq!(pword.to_string().parse(), Ok(pword));
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// Checks for policy lines that can't mean what they say.
+//
+// Errors are lines that make `part2` panic, warnings are lines that one of the policies can
+// never pass, and info is for ranges that are only partly reachable.
+
+use crate::Password;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintKind {
+    // lo > hi, so the count policy never passes.
+    ReversedRange,
+    // The range asks for more letters than the password has.
+    RangeWiderThanPassword,
+    // A position is 0, negative or past the end, which panics in `part2`.
+    PositionOutOfBounds,
+    // lo == hi, so exactly one of the positions can never match.
+    SamePositions,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    // 1-based, like an editor.
+    pub line: usize,
+    pub severity: Severity,
+    pub kind: LintKind,
+    pub message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}: {}", self.line, self.severity, self.message)
+    }
+}
+
+pub fn lint_password(line: usize, pword: &Password) -> Vec<Lint> {
+    let (lo, hi) = pword.range;
+    let len = pword.password.chars().count() as i32;
+    let mut lints = Vec::new();
+    let mut push = |severity, kind, message| {
+        lints.push(Lint {
+            line,
+            severity,
+            kind,
+            message,
+        })
+    };
+
+    if lo > hi {
+        push(
+            Severity::Warning,
+            LintKind::ReversedRange,
+            format!(
+                "range {}-{} is reversed, the count policy never passes",
+                lo, hi
+            ),
+        );
+    }
+
+    if lo > len {
+        push(
+            Severity::Warning,
+            LintKind::RangeWiderThanPassword,
+            format!(
+                "needs at least {} {:?}s but the password is only {} long, the count policy never passes",
+                lo, pword.letter, len
+            ),
+        );
+    } else if hi > len {
+        push(
+            Severity::Info,
+            LintKind::RangeWiderThanPassword,
+            format!(
+                "allows up to {} {:?}s but the password is only {} long",
+                hi, pword.letter, len
+            ),
+        );
+    }
+
+    let positions = if lo == hi { vec![lo] } else { vec![lo, hi] };
+    for pos in positions {
+        if pos < 1 || pos > len {
+            push(
+                Severity::Error,
+                LintKind::PositionOutOfBounds,
+                format!(
+                    "position {} is outside the {} character password, part 2 panics on it",
+                    pos, len
+                ),
+            );
+        }
+    }
+
+    if lo == hi {
+        push(
+            Severity::Warning,
+            LintKind::SamePositions,
+            format!(
+                "both positions are {}, the positional policy never passes",
+                lo
+            ),
+        );
+    }
+
+    lints
+}
+
+pub fn lint(passwords: &[Password]) -> Vec<Lint> {
+    passwords
+        .iter()
+        .enumerate()
+        .flat_map(|(i, pword)| lint_password(i + 1, pword))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_passwords;
+
+    fn kinds(line: &str) -> Vec<(Severity, LintKind)> {
+        lint(&parse_passwords(line))
+            .into_iter()
+            .map(|lint| (lint.severity, lint.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_kinds() {
+        assert_eq!(kinds("1-3 a: abcde"), vec![]);
+        assert_eq!(
+            kinds("5-2 a: abcde"),
+            vec![(Severity::Warning, LintKind::ReversedRange)]
+        );
+        assert_eq!(
+            kinds("2-9 a: abcde"),
+            vec![
+                (Severity::Info, LintKind::RangeWiderThanPassword),
+                (Severity::Error, LintKind::PositionOutOfBounds),
+            ]
+        );
+        assert_eq!(
+            kinds("7-9 a: abcde"),
+            vec![
+                (Severity::Warning, LintKind::RangeWiderThanPassword),
+                (Severity::Error, LintKind::PositionOutOfBounds),
+                (Severity::Error, LintKind::PositionOutOfBounds),
+            ]
+        );
+        assert_eq!(
+            kinds("0-2 a: abcde"),
+            vec![(Severity::Error, LintKind::PositionOutOfBounds)]
+        );
+        assert_eq!(
+            kinds("3-3 a: abcde"),
+            vec![(Severity::Warning, LintKind::SamePositions)]
+        );
+    }
+
+    #[test]
+    fn test_lint_lines() {
+        let lints = lint(&parse_passwords("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccc"));
+        assert_eq!(lints.len(), 2);
+        assert!(lints.iter().all(|lint| lint.line == 3));
+        assert_eq!(
+            lints[1].to_string(),
+            "line 3: error: position 9 is outside the 3 character password, part 2 panics on it"
+        );
+    }
+}
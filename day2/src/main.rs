@@ -1,117 +1,345 @@
+use day2::breach::{self, Format, Wordlist};
+use day2::diff::{self, Diff};
+use day2::graphemes::{self, TextMode};
+use day2::lint::{self, Severity};
+use day2::matching::{self, MatchMode};
+use day2::rules::{self, Rule, RuleError};
+use day2::stats::Stats;
+use day2::stream;
+use day2::strength::{self, StrengthPolicy};
+use day2::{load_file, parse_passwords, Password, Policy};
+use std::env;
 use std::fs::File;
-use std::io::{Read, Result};
+use std::io::{self, BufReader};
 use std::path::Path;
+use std::process;
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Password {
-    pub password: String,
-    pub letter: char,
-    pub range: (i32, i32),
+const USAGE: &str = "Usage: day2 [--graphemes | --match <exact|case-insensitive|case-fold>]
+                 [--lint] [--config <path>] [--rule <rule>] [--rule-file <path>]
+                 [--min-strength <bits>] [--breach-list <path>] [--breach-sha1 <path>]
+                 [--stats [--json]]
+       day2 --stream <path or - for stdin>
+       day2 --diff <old path> <new path>";
+
+#[derive(Default)]
+struct Options {
+    // Extra rules to count, see rules.rs, along with the text or path they came from.
+    rules: Vec<(String, Result<Rule, RuleError>)>,
+    stream: Option<String>,
+    // Old and new dumps to compare, see diff.rs.
+    diff: Option<(String, String)>,
+    config: Option<String>,
+    min_strength: Option<f64>,
+    breach_lists: Vec<(String, Format)>,
+    lint: bool,
+    stats: bool,
+    json: bool,
+    // Count positions and letters in grapheme clusters instead of chars, see graphemes.rs.
+    graphemes: bool,
+    // Match letters with `mode` and allow classes like `\d` as the letter, see matching.rs.
+    matching: Option<MatchMode>,
+}
+
+// Prints a line of the text report. With `--stats --json` stdout is only the JSON, so the text goes
+// to stderr instead.
+macro_rules! report {
+    ($json:expr, $($arg:tt)*) => {
+        if $json {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
 }
 
-impl Password {
-    #[inline(always)]
-    pub fn is_in_range(&self, count: i32) -> bool {
-        if self.range.0 <= count && count <= self.range.1 {
-            return true;
+fn parse_args() -> Option<Options> {
+    let mut options = Options::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lint" => {
+                options.lint = true;
+                continue;
+            }
+            "--stats" => {
+                options.stats = true;
+                continue;
+            }
+            "--json" => {
+                options.json = true;
+                continue;
+            }
+            "--graphemes" => {
+                options.graphemes = true;
+                continue;
+            }
+            _ => {}
+        }
+
+        match (arg.as_str(), args.next()) {
+            ("--rule", Some(rule)) => options.rules.push((rule.clone(), Rule::parse(&rule))),
+            ("--rule-file", Some(path)) => {
+                let rule = rules::load_rule(Path::new(&path));
+                options.rules.push((path, rule));
+            }
+            ("--stream", Some(path)) => options.stream = Some(path),
+            ("--diff", Some(old)) => options.diff = Some((old, args.next()?)),
+            ("--config", Some(path)) => options.config = Some(path),
+            ("--match", Some(mode)) => options.matching = Some(MatchMode::from_name(&mode)?),
+            ("--breach-list", Some(path)) => options.breach_lists.push((path, Format::Plain)),
+            ("--breach-sha1", Some(path)) => options.breach_lists.push((path, Format::Sha1)),
+            ("--min-strength", Some(bits)) => options.min_strength = Some(bits.parse().ok()?),
+            _ => return None,
         }
-        false
     }
+    Some(options)
 }
 
 fn main() {
-    let input = load_file(Path::new("input.txt")).unwrap();
-    println!("Part 1: {:?}", part1(&input));
-    println!("Part 2: {:?}", part2(&input));
-}
+    let options = parse_args().unwrap_or_else(|| {
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    });
 
-fn load_file(path: &Path) -> Result<String> {
-    let mut file = File::open(path)?;
-    let mut input = String::new();
-    file.read_to_string(&mut input)?;
-    Ok(input)
-}
+    if let Some(path) = options.stream {
+        let counts = if path == "-" {
+            stream::count_valid(io::stdin().lock(), stream::DEFAULT_CHUNK_LINES)
+        } else {
+            File::open(&path).and_then(|file| {
+                stream::count_valid(BufReader::new(file), stream::DEFAULT_CHUNK_LINES)
+            })
+        };
+        match counts {
+            Ok(counts) => {
+                println!("Part 1: {:?}", counts.part1);
+                println!("Part 2: {:?}", counts.part2);
+            }
+            Err(err) => {
+                eprintln!("Couldn't validate {}: {}", path, err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
 
-fn parse_passwords(input: &str) -> Vec<Password> {
-    let mut passwords = Vec::new();
-    for line in input.lines() {
-        let split: Vec<&str> = line.split_whitespace().collect();
-        let letter = split[1].chars().next().unwrap();
-        let password = split[2].to_string();
-        let ranges: Vec<&str> = split[0].split('-').collect();
-        let range: (i32, i32) = (ranges[0].parse().unwrap(), ranges[1].parse().unwrap());
-        passwords.push(Password {
-            letter,
-            password,
-            range,
-        });
+    if let Some((old, new)) = options.diff {
+        let load = |path: &str| {
+            load_file(Path::new(path)).unwrap_or_else(|err| {
+                eprintln!("Couldn't read {}: {}", path, err);
+                process::exit(1);
+            })
+        };
+        print_diff(&parse_passwords(&load(&old)), &parse_passwords(&load(&new)));
+        return;
     }
 
-    passwords
-}
+    let input = load_file(Path::new("input.txt")).unwrap();
+    let json = options.stats && options.json;
+    if options.lint {
+        let lints = lint::lint(&parse_passwords(&input));
+        for lint in &lints {
+            report!(json, "{}", lint);
+        }
+        let count = |severity| {
+            lints
+                .iter()
+                .filter(|lint| lint.severity == severity)
+                .count()
+        };
+        report!(
+            json,
+            "Lint: {} errors, {} warnings, {} info\n",
+            count(Severity::Error),
+            count(Severity::Warning),
+            count(Severity::Info)
+        );
+        // Errors are the lines `part2` would panic on. The other counting modes treat out of range
+        // positions as not matching, so only stop if the default one is going to run.
+        let default_counts = options.config.is_none() && options.matching.is_none();
+        if count(Severity::Error) > 0 && default_counts && !options.graphemes {
+            eprintln!("Not counting, part 2 panics on lines with errors");
+            process::exit(1);
+        }
+    }
 
-// Get the number of valid passwords.
-fn part1(input: &str) -> i32 {
-    let mut valid_passwords = 0;
-    let passwords = parse_passwords(input);
+    if let Some(path) = &options.config {
+        run_config(&input, path, json);
+    } else if let Some(mode) = options.matching {
+        report!(
+            json,
+            "Part 1: {:?}",
+            matching::count_valid(&input, Policy::Count, mode)
+        );
+        report!(
+            json,
+            "Part 2: {:?}",
+            matching::count_valid(&input, Policy::Positional, mode)
+        );
+    } else {
+        let mode = if options.graphemes {
+            TextMode::Graphemes
+        } else {
+            TextMode::Chars
+        };
+        report!(
+            json,
+            "Part 1: {:?}",
+            graphemes::count_valid(&input, Policy::Count, mode)
+        );
+        report!(
+            json,
+            "Part 2: {:?}",
+            graphemes::count_valid(&input, Policy::Positional, mode)
+        );
+    }
 
-    for pword in passwords.iter() {
-        let count = pword
-            .password
-            .chars()
-            .fold(0, |acc, c| if c == pword.letter { acc + 1 } else { acc });
+    if let Some(min_bits) = options.min_strength {
+        report!(
+            json,
+            "Strength >= {} bits: {:?}",
+            min_bits,
+            strength::count_valid(&input, StrengthPolicy { min_bits })
+        );
+    }
+
+    for (path, format) in options.breach_lists {
+        let wordlist = Wordlist::load(Path::new(&path), format).unwrap_or_else(|err| {
+            eprintln!("Couldn't load breach list {}: {}", path, err);
+            process::exit(1);
+        });
+        let passwords = parse_passwords(&input);
+        let breached = breach::find_breached(&passwords, &wordlist);
+        report!(json, "Breached in {}: {}", path, breached.len());
+        for (i, pword) in breached {
+            report!(json, "  line {}: {}", i + 1, pword);
+        }
+    }
 
-        if pword.is_in_range(count) {
-            valid_passwords += 1;
+    for (name, rule) in options.rules {
+        match rule {
+            Ok(rule) => report!(
+                json,
+                "Rule {:?}: {}",
+                name,
+                rules::count_matching(&parse_passwords(&input), &rule)
+            ),
+            Err(err) => {
+                eprintln!("Invalid rule: {}", err);
+                process::exit(1);
+            }
         }
     }
 
-    valid_passwords
+    if options.stats {
+        let stats = Stats::new(&parse_passwords(&input));
+        if options.json {
+            print_json(&stats);
+        } else {
+            println!("\n{}", stats);
+        }
+    }
 }
 
-fn part2(input: &str) -> i32 {
-    let mut valid_passwords = 0;
-    let passwords = parse_passwords(input);
+fn print_diff(old: &[Password], new: &[Password]) {
+    let diff = Diff::new(old, new);
+    let validity = |pword: &Password| {
+        diff::POLICIES
+            .iter()
+            .map(|policy| {
+                let valid = if policy.is_valid(pword) {
+                    "valid"
+                } else {
+                    "invalid"
+                };
+                format!("{:?} {}", policy, valid)
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
 
-    for pword in passwords.iter() {
-        let chars: Vec<char> = pword.password.chars().collect();
-        let matches: (bool, bool) = (
-            chars[pword.range.0 as usize - 1] == pword.letter,
-            chars[pword.range.1 as usize - 1] == pword.letter,
+    println!("Added: {}", diff.added.len());
+    for (i, pword) in &diff.added {
+        println!("  line {}: {} ({})", i + 1, pword, validity(pword));
+    }
+    println!("Removed: {}", diff.removed.len());
+    for (i, pword) in &diff.removed {
+        println!("  line {}: {} ({})", i + 1, pword, validity(pword));
+    }
+    println!("Changed: {}", diff.changed().count());
+    for matched in diff.changed() {
+        let (old_line, old) = matched.old;
+        let (new_line, new) = matched.new;
+        let transitions: Vec<String> = diff::POLICIES
+            .iter()
+            .filter_map(|&policy| match matched.validity(policy) {
+                (false, true) => Some(format!("{:?} became valid", policy)),
+                (true, false) => Some(format!("{:?} became invalid", policy)),
+                _ => None,
+            })
+            .collect();
+        let transitions = if transitions.is_empty() {
+            "no change in validity".to_string()
+        } else {
+            transitions.join(", ")
+        };
+        println!(
+            "  line {} -> {}: {} -> {} ({})",
+            old_line + 1,
+            new_line + 1,
+            old,
+            new,
+            transitions
         );
+    }
 
-        // ^ is XOR, meaning only true if exactly one is true, otherwise false.
-        if matches.0 ^ matches.1 {
-            valid_passwords += 1;
-        }
+    for &policy in diff::POLICIES.iter() {
+        let transitions = diff.transitions(policy);
+        println!(
+            "\n{:?}: {} valid -> {} valid",
+            policy,
+            transitions.valid_before(),
+            transitions.valid_after()
+        );
+        println!("  stayed valid: {}", transitions.stayed_valid);
+        println!("  became valid: {}", transitions.became_valid);
+        println!("  became invalid: {}", transitions.became_invalid);
+        println!("  stayed invalid: {}", transitions.stayed_invalid);
+        println!("  added valid: {}", transitions.added_valid);
+        println!("  removed valid: {}", transitions.removed_valid);
     }
+}
 
-    valid_passwords
+#[cfg(feature = "serde")]
+fn print_json(stats: &Stats) {
+    println!("{}", stats.to_json().unwrap());
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_parse_single_password() {
-        let input = "13-15 x: rgnqfdxsvlplxjx\n2-3 g: sxpw".to_string();
-        let password = parse_passwords(&input);
-        assert_eq!(
-            password[0],
-            Password {
-                password: "rgnqfdxsvlplxjx".to_string(),
-                letter: 'x',
-                range: (13, 15),
-            }
-        );
-        assert_eq!(
-            password[1],
-            Password {
-                password: "sxpw".to_string(),
-                letter: 'g',
-                range: (2, 3),
+#[cfg(not(feature = "serde"))]
+fn print_json(_stats: &Stats) {
+    eprintln!("JSON output needs the `serde` feature, try `cargo run --features serde`");
+    process::exit(1);
+}
+
+#[cfg(feature = "config")]
+fn run_config(input: &str, path: &str, json: bool) {
+    use day2::config::{self, Config};
+
+    let checks = Config::load(Path::new(path)).and_then(|config| config.checks());
+    match checks {
+        Ok(checks) => {
+            for (name, count) in config::count_valid(&parse_passwords(input), &checks) {
+                report!(json, "{}: {:?}", name, count);
             }
-        );
+        }
+        Err(err) => {
+            eprintln!("Couldn't load {}: {}", path, err);
+            process::exit(1);
+        }
     }
 }
+
+#[cfg(not(feature = "config"))]
+fn run_config(_input: &str, _path: &str, _json: bool) {
+    eprintln!("Config files need the `config` feature, try `cargo run --features config`");
+    process::exit(1);
+}
@@ -0,0 +1,240 @@
+// Looser ways of matching the policy letter, for exports that mix case or give a character class
+// instead of a letter.
+//
+// The letter field of a line can be a single letter, matched according to a `MatchMode`, or a
+// named class: `\d`, `\w`, `\s`, a POSIX style `[:upper:]` (any name from `CharClass`), or the
+// bare name like `digit`.
+
+use crate::rules::CharClass;
+use crate::Policy;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum MatchMode {
+    Exact,
+    // Ignores case for ascii letters only.
+    CaseInsensitive,
+    // Unicode simple case folding, so 'ſ' matches 's', 'ẞ' matches 'ß', and 'Σ', 'σ' and 'ς' all
+    // match.
+    CaseFold,
+}
+
+impl MatchMode {
+    pub fn from_name(name: &str) -> Option<MatchMode> {
+        match name {
+            "exact" => Some(MatchMode::Exact),
+            "case-insensitive" => Some(MatchMode::CaseInsensitive),
+            "case-fold" => Some(MatchMode::CaseFold),
+            _ => None,
+        }
+    }
+}
+
+// The char if the mapping gives exactly one.
+fn single(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+// Simple folding maps one char to one char. Going through uppercase first is what makes 'ſ' and 'ς'
+// fold to the same thing as 's' and 'σ', and a step that would give more than one char, like 'ß'
+// to "SS", is skipped, so 'ß' stays put and 'ẞ' lowercases to it.
+fn fold(c: char) -> char {
+    let upper = single(c.to_uppercase()).unwrap_or(c);
+    single(upper.to_lowercase()).unwrap_or(upper)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Matcher {
+    Letter(char, MatchMode),
+    Class(CharClass),
+}
+
+impl Matcher {
+    // Parses a line's letter field, or returns `None` if it's neither one letter nor a known class.
+    pub fn parse(field: &str, mode: MatchMode) -> Option<Matcher> {
+        let class = match field {
+            "\\d" => Some(CharClass::Digit),
+            "\\w" => Some(CharClass::Word),
+            "\\s" => Some(CharClass::Whitespace),
+            _ => field
+                .strip_prefix("[:")
+                .and_then(|name| name.strip_suffix(":]"))
+                .map_or_else(|| CharClass::from_name(field), CharClass::from_name),
+        };
+        if let Some(class) = class {
+            return Some(Matcher::Class(class));
+        }
+
+        let mut chars = field.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(Matcher::Letter(c, mode)),
+            _ => None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn matches(&self, c: char) -> bool {
+        match *self {
+            Matcher::Letter(letter, MatchMode::Exact) => c == letter,
+            Matcher::Letter(letter, MatchMode::CaseInsensitive) => c.eq_ignore_ascii_case(&letter),
+            Matcher::Letter(letter, MatchMode::CaseFold) => c == letter || fold(c) == fold(letter),
+            Matcher::Class(class) => class.contains(c),
+        }
+    }
+
+    // Number of chars in `password` that match.
+    pub fn count(&self, password: &str) -> i32 {
+        match *self {
+            Matcher::Letter(letter, MatchMode::Exact) => {
+                crate::scan::count_letter(password, letter)
+            }
+            _ => password
+                .chars()
+                .fold(0, |acc, c| if self.matches(c) { acc + 1 } else { acc }),
+        }
+    }
+
+    // Whether the char at the 0-based `index` matches. Indices outside the password never match.
+    pub fn matches_at(&self, password: &str, index: i32) -> bool {
+        index >= 0
+            && password
+                .chars()
+                .nth(index as usize)
+                .is_some_and(|c| self.matches(c))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedPassword {
+    pub password: String,
+    pub matcher: Matcher,
+    pub range: (i32, i32),
+}
+
+impl MatchedPassword {
+    pub fn count_matches(&self) -> i32 {
+        self.matcher.count(&self.password)
+    }
+
+    // Whether the character at the 1-based position `pos` matches. Positions outside the password
+    // never match.
+    pub fn matches_at(&self, pos: i32) -> bool {
+        pos >= 1 && self.matcher.matches_at(&self.password, pos - 1)
+    }
+
+    pub fn is_valid(&self, policy: Policy) -> bool {
+        match policy {
+            Policy::Count => {
+                let count = self.count_matches();
+                self.range.0 <= count && count <= self.range.1
+            }
+            Policy::Positional => self.matches_at(self.range.0) ^ self.matches_at(self.range.1),
+        }
+    }
+}
+
+// Parses a `lo-hi letter: password` line, or returns `None` if it's malformed or the letter field
+// is neither one letter nor a known class.
+pub fn parse_line(line: &str, mode: MatchMode) -> Option<MatchedPassword> {
+    let fields = crate::split_line(line).ok()?;
+    Some(MatchedPassword {
+        password: fields.password.to_string(),
+        matcher: Matcher::parse(fields.letter, mode)?,
+        range: fields.range,
+    })
+}
+
+pub fn parse_passwords(input: &str, mode: MatchMode) -> Vec<MatchedPassword> {
+    input
+        .lines()
+        .map(|line| {
+            parse_line(line, mode).unwrap_or_else(|| panic!("Malformed password line {:?}", line))
+        })
+        .collect()
+}
+
+// Get the number of passwords passing `policy` with letters matched by `mode`.
+pub fn count_valid(input: &str, policy: Policy, mode: MatchMode) -> i32 {
+    parse_passwords(input, mode).iter().fold(
+        0,
+        |acc, pword| if pword.is_valid(policy) { acc + 1 } else { acc },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_file, part1, part2};
+    use std::path::Path;
+
+    #[test]
+    fn test_parse_matcher() {
+        let mode = MatchMode::Exact;
+        assert_eq!(Matcher::parse("a", mode), Some(Matcher::Letter('a', mode)));
+        assert_eq!(
+            Matcher::parse("\\d", mode),
+            Some(Matcher::Class(CharClass::Digit))
+        );
+        assert_eq!(
+            Matcher::parse("[:upper:]", mode),
+            Some(Matcher::Class(CharClass::Upper))
+        );
+        assert_eq!(
+            Matcher::parse("digit", mode),
+            Some(Matcher::Class(CharClass::Digit))
+        );
+        assert_eq!(Matcher::parse("[:emoji:]", mode), None);
+        assert_eq!(Matcher::parse("ab", mode), None);
+    }
+
+    #[test]
+    fn test_match_modes() {
+        let letter = |c, mode| Matcher::Letter(c, mode);
+        assert!(!letter('a', MatchMode::Exact).matches('A'));
+        assert!(letter('a', MatchMode::CaseInsensitive).matches('A'));
+        assert!(!letter('\u{e9}', MatchMode::CaseInsensitive).matches('\u{c9}'));
+        assert!(letter('\u{e9}', MatchMode::CaseFold).matches('\u{c9}'));
+        // Long s and final sigma only match their plain forms with case folding.
+        assert!(letter('s', MatchMode::CaseFold).matches('\u{17f}'));
+        assert!(!letter('s', MatchMode::CaseInsensitive).matches('\u{17f}'));
+        assert!(letter('\u{3c3}', MatchMode::CaseFold).matches('\u{3c2}'));
+        assert!(letter('\u{3c3}', MatchMode::CaseFold).matches('\u{3a3}'));
+        // Capital sharp s folds to 'ß', which has no single char uppercase and folds to itself.
+        assert!(letter('\u{df}', MatchMode::CaseFold).matches('\u{1e9e}'));
+        assert!(letter('\u{1e9e}', MatchMode::CaseFold).matches('\u{df}'));
+        assert!(!letter('\u{df}', MatchMode::CaseFold).matches('s'));
+        // Dotted capital I only has a multi-char lowercase, so it doesn't fold onto 'i'.
+        assert!(!letter('i', MatchMode::CaseFold).matches('\u{130}'));
+    }
+
+    #[test]
+    fn test_count_and_positional_modes() {
+        let input = "1-2 a: AbAcd\n2-3 \\d: a1b2c3\n1-2 [:upper:]: Ab";
+        let count = |mode| count_valid(input, Policy::Count, mode);
+        let positional = |mode| count_valid(input, Policy::Positional, mode);
+
+        // Exact: no 'a' at all in "AbAcd", but the digit and capital lines pass either way.
+        assert_eq!(count(MatchMode::Exact), 2);
+        assert_eq!(count(MatchMode::CaseInsensitive), 3);
+        // Positions: "AbAcd" has A at 1 only, "a1b2c3" a digit at 2 only, "Ab" a capital at 1.
+        assert_eq!(positional(MatchMode::Exact), 2);
+        assert_eq!(positional(MatchMode::CaseFold), 3);
+    }
+
+    #[test]
+    fn test_exact_matches_parts() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        assert_eq!(
+            count_valid(&input, Policy::Count, MatchMode::Exact),
+            part1(&input)
+        );
+        assert_eq!(
+            count_valid(&input, Policy::Positional, MatchMode::Exact),
+            part2(&input)
+        );
+    }
+}
@@ -0,0 +1,268 @@
+// Suggests the fewest character edits that make an invalid password pass a policy.
+//
+// Every edit changes the letter count by at most one and touches at most one position, so:
+//  - Count: short by k letters needs k edits (replace other characters, append if there aren't
+//    enough), over by k needs k removals.
+//  - Positional: both positions matching needs one replacement, neither matching needs one
+//    replacement if a position is inside the password, otherwise it gets padded out to reach it.
+
+use crate::{Password, Policy};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edit {
+    Replace { index: usize, with: char },
+    Remove { index: usize },
+    Insert { index: usize, c: char },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repair {
+    pub edits: Vec<Edit>,
+    pub repaired: Password,
+}
+
+// Applies `edits` in order. Indices are 0-based character indices into the password as it is
+// when that edit is applied, so earlier edits can shift where later ones land.
+pub fn apply_edits(password: &str, edits: &[Edit]) -> String {
+    let mut chars: Vec<char> = password.chars().collect();
+    for edit in edits {
+        match *edit {
+            Edit::Replace { index, with } => chars[index] = with,
+            Edit::Remove { index } => {
+                chars.remove(index);
+            }
+            Edit::Insert { index, c } => chars.insert(index, c),
+        }
+    }
+    chars.into_iter().collect()
+}
+
+// Returns the edits needed to make `pword` pass `policy`, which are empty if it already passes,
+// or `None` if no edits can ever make it pass (like a reversed range, or lo == hi for positions).
+pub fn repair(pword: &Password, policy: Policy) -> Option<Repair> {
+    let edits = match policy {
+        Policy::Count => repair_count(pword)?,
+        Policy::Positional => repair_positional(pword)?,
+    };
+
+    Some(Repair {
+        repaired: Password {
+            password: apply_edits(&pword.password, &edits),
+            ..pword.clone()
+        },
+        edits,
+    })
+}
+
+// Any character that isn't the policy letter, for padding and for knocking out a match.
+fn filler(letter: char) -> char {
+    if letter == 'a' {
+        'b'
+    } else {
+        'a'
+    }
+}
+
+fn repair_count(pword: &Password) -> Option<Vec<Edit>> {
+    let (lo, hi) = pword.range;
+    if lo > hi || hi < 0 {
+        return None;
+    }
+
+    let count = pword.count_letter();
+    if count < lo {
+        let missing = (lo - count) as usize;
+        let mut edits: Vec<Edit> = pword
+            .password
+            .chars()
+            .enumerate()
+            .filter(|&(_, c)| c != pword.letter)
+            .take(missing)
+            .map(|(index, _)| Edit::Replace {
+                index,
+                with: pword.letter,
+            })
+            .collect();
+
+        let len = pword.password.chars().count();
+        for index in len..len + missing - edits.len() {
+            edits.push(Edit::Insert {
+                index,
+                c: pword.letter,
+            });
+        }
+        Some(edits)
+    } else if count > hi {
+        // Remove from the back so the indices of the remaining removals don't move.
+        let extra = (count - hi) as usize;
+        let mut indices: Vec<usize> = pword
+            .password
+            .chars()
+            .enumerate()
+            .filter(|&(_, c)| c == pword.letter)
+            .map(|(index, _)| index)
+            .collect();
+        indices.reverse();
+        Some(
+            indices
+                .into_iter()
+                .take(extra)
+                .map(|index| Edit::Remove { index })
+                .collect(),
+        )
+    } else {
+        Some(Vec::new())
+    }
+}
+
+fn repair_positional(pword: &Password) -> Option<Vec<Edit>> {
+    let (lo, hi) = pword.range;
+    if lo == hi || (lo < 1 && hi < 1) {
+        return None;
+    }
+
+    match (pword.letter_at(lo), pword.letter_at(hi)) {
+        (true, true) => Some(vec![Edit::Replace {
+            index: hi as usize - 1,
+            with: filler(pword.letter),
+        }]),
+        (false, false) => {
+            let len = pword.password.chars().count();
+            let in_password = |pos: i32| pos >= 1 && pos as usize <= len;
+            if in_password(lo) || in_password(hi) {
+                let pos = if in_password(lo) { lo } else { hi };
+                return Some(vec![Edit::Replace {
+                    index: pos as usize - 1,
+                    with: pword.letter,
+                }]);
+            }
+
+            // Both positions are past the end, pad up to the nearer one.
+            let pos = if lo < 1 || (hi >= 1 && hi < lo) {
+                hi
+            } else {
+                lo
+            } as usize;
+            let mut edits: Vec<Edit> = (len..pos - 1)
+                .map(|index| Edit::Insert {
+                    index,
+                    c: filler(pword.letter),
+                })
+                .collect();
+            edits.push(Edit::Insert {
+                index: pos - 1,
+                c: pword.letter,
+            });
+            Some(edits)
+        }
+        _ => Some(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn pword(line: &str) -> Password {
+        line.parse().unwrap()
+    }
+
+    #[test]
+    fn test_repair_count() {
+        let repair_count = |line| repair(&pword(line), Policy::Count).unwrap();
+
+        let fixed = repair_count("3-4 a: abcde");
+        assert_eq!(
+            fixed.edits,
+            vec![
+                Edit::Replace {
+                    index: 1,
+                    with: 'a'
+                },
+                Edit::Replace {
+                    index: 2,
+                    with: 'a'
+                }
+            ]
+        );
+        assert_eq!(fixed.repaired, pword("3-4 a: aaade"));
+
+        let fixed = repair_count("1-2 b: bbbb");
+        assert_eq!(
+            fixed.edits,
+            vec![Edit::Remove { index: 3 }, Edit::Remove { index: 2 }]
+        );
+        assert_eq!(fixed.repaired, pword("1-2 b: bb"));
+
+        let fixed = repair_count("4-5 c: cc");
+        assert_eq!(
+            fixed.edits,
+            vec![
+                Edit::Insert { index: 2, c: 'c' },
+                Edit::Insert { index: 3, c: 'c' }
+            ]
+        );
+
+        assert!(repair_count("1-3 a: abcde").edits.is_empty());
+        assert_eq!(repair(&pword("5-2 a: abcde"), Policy::Count), None);
+    }
+
+    #[test]
+    fn test_repair_positional() {
+        let repair_pos = |line| repair(&pword(line), Policy::Positional).unwrap();
+
+        let fixed = repair_pos("1-3 b: cdefg");
+        assert_eq!(
+            fixed.edits,
+            vec![Edit::Replace {
+                index: 0,
+                with: 'b'
+            }]
+        );
+        assert_eq!(fixed.repaired, pword("1-3 b: bdefg"));
+
+        let fixed = repair_pos("2-9 c: ccccccccc");
+        assert_eq!(
+            fixed.edits,
+            vec![Edit::Replace {
+                index: 8,
+                with: 'a'
+            }]
+        );
+
+        let fixed = repair_pos("5-7 x: ab");
+        assert_eq!(fixed.repaired, pword("5-7 x: abaax"));
+        assert_eq!(fixed.edits.len(), 3);
+        assert_eq!(repair_pos("4-0 x: ab").repaired, pword("4-0 x: abax"));
+
+        assert!(repair_pos("1-3 a: abcde").edits.is_empty());
+        assert_eq!(repair(&pword("2-2 a: abcde"), Policy::Positional), None);
+    }
+
+    proptest! {
+        #[test]
+        fn test_repaired_passwords_are_valid(
+            password in "[abc]{0,12}",
+            letter in "[abc]",
+            lo in 0..15_i32,
+            width in 0..15_i32,
+        ) {
+            let letter = letter.chars().next().unwrap();
+            let pword = Password { password, letter, range: (lo, lo + width) };
+
+            for &policy in [Policy::Count, Policy::Positional].iter() {
+                if let Some(fixed) = repair(&pword, policy) {
+                    prop_assert!(policy.is_valid(&fixed.repaired));
+                    prop_assert_eq!(fixed.edits.is_empty(), policy.is_valid(&pword));
+                }
+            }
+
+            // The count repair is minimal, one edit per letter it's out by.
+            let count = pword.count_letter();
+            let distance = (lo - count).max(count - lo - width).max(0);
+            let fixed = repair(&pword, Policy::Count).unwrap();
+            prop_assert_eq!(fixed.edits.len() as i32, distance);
+        }
+    }
+}
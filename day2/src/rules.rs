@@ -0,0 +1,528 @@
+// A small rule language for combining password policies without writing new Rust.
+//
+// Rules are made of predicates joined with AND, OR and NOT (case insensitive), with parentheses
+// for grouping. NOT binds tightest, then AND, then OR. The predicates are:
+//
+//   count            the letter appears within the line's own range (the part 1 rule)
+//   count(lo, hi)    the letter appears between lo and hi times, inclusive
+//   pos(n)           the letter is at 1-based position n, where n can also be `lo` or `hi`
+//   len(lo, hi)      the password is between lo and hi characters long, inclusive
+//   class(name)      the password contains a character of the class, one of
+//                    digit, alpha, upper, lower, alnum, word, punct or space
+//
+// For example, the part 2 rule is `(pos(lo) AND NOT pos(hi)) OR (pos(hi) AND NOT pos(lo))`.
+// Rule files can be split over several lines, and anything after a `#` is a comment.
+
+use crate::Password;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CharClass {
+    // Only ascii 0-9, like `Punctuation`, so fractions and superscripts don't count.
+    Digit,
+    Alpha,
+    Upper,
+    Lower,
+    Alphanumeric,
+    // Alphanumeric or `_`, like regex's `\w`.
+    Word,
+    Punctuation,
+    Whitespace,
+}
+
+impl CharClass {
+    pub fn from_name(name: &str) -> Option<CharClass> {
+        match name {
+            "digit" => Some(CharClass::Digit),
+            "alpha" => Some(CharClass::Alpha),
+            "upper" => Some(CharClass::Upper),
+            "lower" => Some(CharClass::Lower),
+            "alnum" => Some(CharClass::Alphanumeric),
+            "word" => Some(CharClass::Word),
+            "punct" => Some(CharClass::Punctuation),
+            "space" => Some(CharClass::Whitespace),
+            _ => None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            CharClass::Digit => c.is_ascii_digit(),
+            CharClass::Alpha => c.is_alphabetic(),
+            CharClass::Upper => c.is_uppercase(),
+            CharClass::Lower => c.is_lowercase(),
+            CharClass::Alphanumeric => c.is_alphanumeric(),
+            CharClass::Word => c.is_alphanumeric() || c == '_',
+            CharClass::Punctuation => c.is_ascii_punctuation(),
+            CharClass::Whitespace => c.is_whitespace(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Position {
+    Lo,
+    Hi,
+    At(i32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    CountInPolicy,
+    CountInRange(i32, i32),
+    LetterAt(Position),
+    Length(i32, i32),
+    Contains(CharClass),
+}
+
+impl Predicate {
+    pub fn matches(&self, pword: &Password) -> bool {
+        match self {
+            Predicate::CountInPolicy => pword.is_in_range(pword.count_letter()),
+            Predicate::CountInRange(lo, hi) => (*lo..=*hi).contains(&pword.count_letter()),
+            Predicate::LetterAt(position) => pword.letter_at(match position {
+                Position::Lo => pword.range.0,
+                Position::Hi => pword.range.1,
+                Position::At(pos) => *pos,
+            }),
+            Predicate::Length(lo, hi) => {
+                (*lo..=*hi).contains(&(pword.password.chars().count() as i32))
+            }
+            Predicate::Contains(class) => pword.password.chars().any(|c| class.contains(c)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    Predicate(Predicate),
+    Not(Box<Rule>),
+    And(Box<Rule>, Box<Rule>),
+    Or(Box<Rule>, Box<Rule>),
+}
+
+impl Rule {
+    pub fn parse(input: &str) -> Result<Rule, RuleError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            next: 0,
+            end: input.len(),
+        };
+        let rule = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(rule),
+            Some((offset, token)) => Err(RuleError::syntax(
+                offset,
+                format!("expected AND, OR or end of rule, found {}", token),
+            )),
+        }
+    }
+
+    pub fn matches(&self, pword: &Password) -> bool {
+        match self {
+            Rule::Predicate(predicate) => predicate.matches(pword),
+            Rule::Not(rule) => !rule.matches(pword),
+            Rule::And(left, right) => left.matches(pword) && right.matches(pword),
+            Rule::Or(left, right) => left.matches(pword) || right.matches(pword),
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::parse(s)
+    }
+}
+
+#[derive(Debug)]
+pub enum RuleError {
+    // `offset` is the byte offset into the rule text where things went wrong.
+    Syntax { offset: usize, message: String },
+    Io(io::Error),
+}
+
+impl RuleError {
+    fn syntax(offset: usize, message: String) -> RuleError {
+        RuleError::Syntax { offset, message }
+    }
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::Syntax { offset, message } => {
+                write!(f, "syntax error at offset {}: {}", offset, message)
+            }
+            RuleError::Io(err) => write!(f, "couldn't read rule file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+impl From<io::Error> for RuleError {
+    fn from(err: io::Error) -> Self {
+        RuleError::Io(err)
+    }
+}
+
+pub fn load_rule(path: &Path) -> Result<Rule, RuleError> {
+    Rule::parse(&fs::read_to_string(path)?)
+}
+
+pub fn count_matching(passwords: &[Password], rule: &Rule) -> i32 {
+    passwords.iter().fold(
+        0,
+        |acc, pword| if rule.matches(pword) { acc + 1 } else { acc },
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(i32),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "{:?}", ident),
+            Token::Number(number) => write!(f, "{}", number),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::Comma => write!(f, "','"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, RuleError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '(' => tokens.push((offset, Token::LParen)),
+            ')' => tokens.push((offset, Token::RParen)),
+            ',' => tokens.push((offset, Token::Comma)),
+            '#' => {
+                // Comment, skip to the end of the line.
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            c if c.is_whitespace() => {}
+            c if c.is_ascii_digit() => {
+                let mut end = offset + 1;
+                while let Some((i, _)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    end = i + 1;
+                }
+                let number = input[offset..end].parse().map_err(|_| {
+                    RuleError::syntax(
+                        offset,
+                        format!("number {} is too large", &input[offset..end]),
+                    )
+                })?;
+                tokens.push((offset, Token::Number(number)));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = offset + c.len_utf8();
+                while let Some((i, c)) = chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_') {
+                    end = i + c.len_utf8();
+                }
+                tokens.push((offset, Token::Ident(input[offset..end].to_string())));
+            }
+            c => {
+                return Err(RuleError::syntax(
+                    offset,
+                    format!("unexpected character {:?}", c),
+                ))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    next: usize,
+    // Offset reported for errors at the end of the input.
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<(usize, &'a Token)> {
+        self.tokens
+            .get(self.next)
+            .map(|(offset, token)| (*offset, token))
+    }
+
+    fn bump(&mut self) -> Option<(usize, &'a Token)> {
+        let token = self.peek();
+        self.next += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some((_, Token::Ident(ident))) if ident.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), RuleError> {
+        match self.bump() {
+            Some((_, token)) if *token == expected => Ok(()),
+            Some((offset, token)) => Err(RuleError::syntax(
+                offset,
+                format!("expected {}, found {}", expected, token),
+            )),
+            None => Err(RuleError::syntax(
+                self.end,
+                format!("expected {}, found end of rule", expected),
+            )),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Rule, RuleError> {
+        let mut rule = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.bump();
+            rule = Rule::Or(Box::new(rule), Box::new(self.parse_and()?));
+        }
+        Ok(rule)
+    }
+
+    fn parse_and(&mut self) -> Result<Rule, RuleError> {
+        let mut rule = self.parse_unary()?;
+        while self.peek_keyword("and") {
+            self.bump();
+            rule = Rule::And(Box::new(rule), Box::new(self.parse_unary()?));
+        }
+        Ok(rule)
+    }
+
+    fn parse_unary(&mut self) -> Result<Rule, RuleError> {
+        if self.peek_keyword("not") {
+            self.bump();
+            return Ok(Rule::Not(Box::new(self.parse_unary()?)));
+        }
+
+        match self.bump() {
+            Some((_, Token::LParen)) => {
+                let rule = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Ok(rule)
+            }
+            Some((offset, Token::Ident(name))) => {
+                Ok(Rule::Predicate(self.parse_predicate(offset, name)?))
+            }
+            Some((offset, token)) => Err(RuleError::syntax(
+                offset,
+                format!("expected a predicate, NOT or '(', found {}", token),
+            )),
+            None => Err(RuleError::syntax(
+                self.end,
+                "expected a predicate, found end of rule".to_string(),
+            )),
+        }
+    }
+
+    fn parse_predicate(&mut self, offset: usize, name: &str) -> Result<Predicate, RuleError> {
+        match name.to_ascii_lowercase().as_str() {
+            "count" if !matches!(self.peek(), Some((_, Token::LParen))) => {
+                Ok(Predicate::CountInPolicy)
+            }
+            "count" => {
+                let (lo, hi) = self.parse_range()?;
+                Ok(Predicate::CountInRange(lo, hi))
+            }
+            "len" => {
+                let (lo, hi) = self.parse_range()?;
+                Ok(Predicate::Length(lo, hi))
+            }
+            "pos" => {
+                self.expect(Token::LParen)?;
+                let position = match self.bump() {
+                    Some((_, Token::Number(pos))) if *pos >= 1 => Position::At(*pos),
+                    Some((_, Token::Ident(ident))) if ident.eq_ignore_ascii_case("lo") => {
+                        Position::Lo
+                    }
+                    Some((_, Token::Ident(ident))) if ident.eq_ignore_ascii_case("hi") => {
+                        Position::Hi
+                    }
+                    Some((offset, token)) => {
+                        return Err(RuleError::syntax(
+                            offset,
+                            format!("expected a position from 1, `lo` or `hi`, found {}", token),
+                        ))
+                    }
+                    None => {
+                        return Err(RuleError::syntax(
+                            self.end,
+                            "expected a position, found end of rule".to_string(),
+                        ))
+                    }
+                };
+                self.expect(Token::RParen)?;
+                Ok(Predicate::LetterAt(position))
+            }
+            "class" => {
+                self.expect(Token::LParen)?;
+                let class = match self.bump() {
+                    Some((offset, Token::Ident(ident))) => {
+                        CharClass::from_name(&ident.to_ascii_lowercase()).ok_or_else(|| {
+                            RuleError::syntax(
+                                offset,
+                                format!("unknown character class {:?}, expected one of digit, alpha, upper, lower, alnum, word, punct or space", ident),
+                            )
+                        })?
+                    }
+                    Some((offset, token)) => {
+                        return Err(RuleError::syntax(
+                            offset,
+                            format!("expected a character class, found {}", token),
+                        ))
+                    }
+                    None => {
+                        return Err(RuleError::syntax(
+                            self.end,
+                            "expected a character class, found end of rule".to_string(),
+                        ))
+                    }
+                };
+                self.expect(Token::RParen)?;
+                Ok(Predicate::Contains(class))
+            }
+            _ => Err(RuleError::syntax(
+                offset,
+                format!(
+                    "unknown predicate {:?}, expected one of count, pos, len or class",
+                    name
+                ),
+            )),
+        }
+    }
+
+    // Offset of the next token, or the end of the input if there isn't one.
+    fn offset(&self) -> usize {
+        self.peek().map_or(self.end, |(offset, _)| offset)
+    }
+
+    fn parse_number(&mut self) -> Result<i32, RuleError> {
+        match self.bump() {
+            Some((_, Token::Number(number))) => Ok(*number),
+            Some((offset, token)) => Err(RuleError::syntax(
+                offset,
+                format!("expected a number, found {}", token),
+            )),
+            None => Err(RuleError::syntax(
+                self.end,
+                "expected a number, found end of rule".to_string(),
+            )),
+        }
+    }
+
+    fn parse_range(&mut self) -> Result<(i32, i32), RuleError> {
+        self.expect(Token::LParen)?;
+        let offset = self.offset();
+        let lo = self.parse_number()?;
+        self.expect(Token::Comma)?;
+        let hi = self.parse_number()?;
+        self.expect(Token::RParen)?;
+        if lo > hi {
+            return Err(RuleError::syntax(
+                offset,
+                format!("range {}, {} is reversed", lo, hi),
+            ));
+        }
+        Ok((lo, hi))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_file, parse_passwords, part1, part2};
+
+    #[test]
+    fn test_parse_rule() {
+        let rule = Rule::parse("count(1, 3) and not pos(1)").unwrap();
+        assert_eq!(
+            rule,
+            Rule::And(
+                Box::new(Rule::Predicate(Predicate::CountInRange(1, 3))),
+                Box::new(Rule::Not(Box::new(Rule::Predicate(Predicate::LetterAt(
+                    Position::At(1)
+                ))))),
+            )
+        );
+
+        // AND binds tighter than OR.
+        let rule: Rule = "class(digit) OR len(1, 2) AND count".parse().unwrap();
+        assert_eq!(
+            rule,
+            Rule::Or(
+                Box::new(Rule::Predicate(Predicate::Contains(CharClass::Digit))),
+                Box::new(Rule::And(
+                    Box::new(Rule::Predicate(Predicate::Length(1, 2))),
+                    Box::new(Rule::Predicate(Predicate::CountInPolicy)),
+                )),
+            )
+        );
+    }
+
+    #[test]
+    fn test_rule_errors() {
+        let err = |rule: &str| match Rule::parse(rule) {
+            Err(RuleError::Syntax { offset, message }) => (offset, message),
+            other => panic!("Expected a syntax error, got {:?}", other),
+        };
+
+        assert_eq!(err("count AND").0, 9);
+        assert_eq!(err("count(3, 1)").0, 6);
+        assert_eq!(err("pos(0)").0, 4);
+        assert_eq!(err("(count").0, 6);
+        assert_eq!(err("count count").0, 6);
+        assert!(err("class(emoji)").1.contains("unknown character class"));
+        assert!(err("size(1, 2)").1.contains("unknown predicate"));
+        assert!(err("count & pos(1)").1.contains("unexpected character '&'"));
+    }
+
+    #[test]
+    fn test_rules_match_parts() {
+        let input = load_file(std::path::Path::new("input.txt")).unwrap();
+        let passwords = parse_passwords(&input);
+
+        let rule = Rule::parse("count").unwrap();
+        assert_eq!(count_matching(&passwords, &rule), part1(&input));
+
+        let rule = Rule::parse(
+            "# exactly one of the two positions\n\
+             (pos(lo) AND NOT pos(hi))\n\
+             OR (pos(hi) AND NOT pos(lo))",
+        )
+        .unwrap();
+        assert_eq!(count_matching(&passwords, &rule), part2(&input));
+    }
+
+    #[test]
+    fn test_rule_matches() {
+        let pword = &parse_passwords("1-3 a: abcde1")[0];
+        let matches = |rule: &str| Rule::parse(rule).unwrap().matches(pword);
+
+        assert!(matches("count AND pos(1)"));
+        assert!(!matches("count AND NOT pos(1)"));
+        assert!(matches("count(1, 1) AND len(6, 6)"));
+        assert!(matches("class(digit) AND NOT class(upper)"));
+        assert!(matches("pos(lo) OR pos(hi)"));
+        assert!(!matches("pos(hi) OR pos(40)"));
+
+        let pword = &parse_passwords("1-3 a: a\u{bd}b\u{b2}")[0];
+        assert!(!Rule::parse("class(digit)").unwrap().matches(pword));
+    }
+}
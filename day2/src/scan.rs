@@ -0,0 +1,133 @@
+// Fast letter counting for long passwords.
+//
+// An ascii byte never shows up inside a multi-byte UTF-8 sequence, so for an ascii letter counting
+// matching bytes gives the same answer as counting chars, even when the password itself isn't
+// ascii. The byte loop is written so it autovectorizes: each chunk compares into a row of per-lane
+// counters, which only get summed every 255 chunks before they could overflow. Non-ascii letters
+// fall back to going through `chars()`.
+
+const LANES: usize = 32;
+
+// Number of times `letter` appears in `haystack`, same as `count_letter_chars`.
+#[inline]
+pub fn count_letter(haystack: &str, letter: char) -> i32 {
+    if letter.is_ascii() {
+        count_byte(haystack.as_bytes(), letter as u8) as i32
+    } else {
+        count_letter_chars(haystack, letter)
+    }
+}
+
+// The plain `char` version, for non-ascii letters.
+pub fn count_letter_chars(haystack: &str, letter: char) -> i32 {
+    haystack
+        .chars()
+        .fold(0, |acc, c| if c == letter { acc + 1 } else { acc })
+}
+
+pub fn count_byte(haystack: &[u8], needle: u8) -> usize {
+    // Passwords are usually short, and below a chunk the setup isn't worth it.
+    if haystack.len() < LANES {
+        return count_byte_scalar(haystack, needle);
+    }
+
+    let mut chunks = haystack.chunks_exact(LANES);
+    let mut count = 0;
+    let mut lanes = [0u8; LANES];
+    let mut pending = 0;
+    for chunk in &mut chunks {
+        for (lane, &b) in lanes.iter_mut().zip(chunk) {
+            *lane += (b == needle) as u8;
+        }
+        pending += 1;
+        if pending == u8::MAX {
+            count += lanes.iter().map(|&lane| lane as usize).sum::<usize>();
+            lanes = [0; LANES];
+            pending = 0;
+        }
+    }
+    count += lanes.iter().map(|&lane| lane as usize).sum::<usize>();
+    count + count_byte_scalar(chunks.remainder(), needle)
+}
+
+fn count_byte_scalar(haystack: &[u8], needle: u8) -> usize {
+    haystack.iter().filter(|&&b| b == needle).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use test::Bencher;
+
+    // Long ascii passwords, with `letter` showing up about one time in four.
+    fn long_password(len: usize, letter: u8) -> String {
+        let mut next = crate::tests::lcg();
+        (0..len)
+            .map(|_| {
+                let r = next() as u8;
+                if r.is_multiple_of(4) {
+                    letter as char
+                } else {
+                    (b'a' + r % 26) as char
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_boundaries() {
+        for len in [0, 1, 31, 32, 33, 64, 255 * 32, 255 * 32 + 1, 300 * 32 + 7] {
+            let password = long_password(len, b'x');
+            assert_eq!(
+                count_letter(&password, 'x'),
+                count_letter_chars(&password, 'x'),
+                "length {}",
+                len
+            );
+        }
+        // All matches, so the lane counters have to roll over before they overflow.
+        let password = "x".repeat(255 * 32 * 3 + 5);
+        assert_eq!(count_letter(&password, 'x'), password.len() as i32);
+    }
+
+    #[test]
+    fn test_non_ascii() {
+        let password = "h\u{e9}llo w\u{f6}rld, \u{e9}\u{e9} and some padding to pass a chunk";
+        assert_eq!(count_letter(password, '\u{e9}'), 3);
+        assert_eq!(count_letter(password, 'l'), 3);
+        // 'Ã' is 0xC3, the lead byte of é and ö, but it never matches them.
+        assert_eq!(count_letter(password, '\u{c3}'), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_matches_chars(password in ".{0,200}", letter in any::<char>()) {
+            prop_assert_eq!(
+                count_letter(&password, letter),
+                count_letter_chars(&password, letter)
+            );
+        }
+
+        #[test]
+        fn test_matches_chars_mixed(password in "[a-d\u{e9}]{0,200}", letter in "[a-d\u{e9}]") {
+            let letter = letter.chars().next().unwrap();
+            prop_assert_eq!(
+                count_letter(&password, letter),
+                count_letter_chars(&password, letter)
+            );
+        }
+    }
+
+    #[bench]
+    fn bench_count_chars(b: &mut Bencher) {
+        let password = long_password(64 * 1024, b'x');
+        b.iter(|| count_letter_chars(test::black_box(&password), 'x'))
+    }
+
+    #[bench]
+    fn bench_count_bytes(b: &mut Bencher) {
+        let password = long_password(64 * 1024, b'x');
+        b.iter(|| count_letter(test::black_box(&password), 'x'))
+    }
+}
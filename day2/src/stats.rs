@@ -0,0 +1,211 @@
+// Statistics over a whole password database, printed as text tables or, with the `serde`
+// feature, as JSON.
+
+use crate::{Password, Policy};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stats {
+    pub total: usize,
+    // How often each letter is the policy letter.
+    pub policy_letters: BTreeMap<char, usize>,
+    // How often each character appears across all passwords.
+    pub password_chars: BTreeMap<char, usize>,
+    // Keyed by hi - lo, in i64 since that can overflow an i32.
+    pub range_widths: BTreeMap<i64, usize>,
+    // Keyed by length in characters.
+    pub password_lengths: BTreeMap<usize, usize>,
+    pub count_valid: usize,
+    pub positional_valid: usize,
+    pub agreement: Agreement,
+}
+
+// How the two policies' verdicts line up.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Agreement {
+    pub both_valid: usize,
+    pub count_only: usize,
+    pub positional_only: usize,
+    pub neither_valid: usize,
+}
+
+impl Agreement {
+    pub fn disagreements(&self) -> usize {
+        self.count_only + self.positional_only
+    }
+}
+
+impl Stats {
+    pub fn new(passwords: &[Password]) -> Stats {
+        let mut stats = Stats {
+            total: passwords.len(),
+            ..Stats::default()
+        };
+
+        for pword in passwords {
+            *stats.policy_letters.entry(pword.letter).or_insert(0) += 1;
+            for c in pword.password.chars() {
+                *stats.password_chars.entry(c).or_insert(0) += 1;
+            }
+            *stats
+                .range_widths
+                .entry(pword.range.1 as i64 - pword.range.0 as i64)
+                .or_insert(0) += 1;
+            *stats
+                .password_lengths
+                .entry(pword.password.chars().count())
+                .or_insert(0) += 1;
+
+            let agreement = &mut stats.agreement;
+            match (
+                Policy::Count.is_valid(pword),
+                Policy::Positional.is_valid(pword),
+            ) {
+                (true, true) => agreement.both_valid += 1,
+                (true, false) => agreement.count_only += 1,
+                (false, true) => agreement.positional_only += 1,
+                (false, false) => agreement.neither_valid += 1,
+            }
+        }
+
+        stats.count_valid = stats.agreement.both_valid + stats.agreement.count_only;
+        stats.positional_valid = stats.agreement.both_valid + stats.agreement.positional_only;
+        stats
+    }
+
+    // Fraction of passwords passing `policy`, or 0 for an empty database.
+    pub fn pass_rate(&self, policy: Policy) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        let valid = match policy {
+            Policy::Count => self.count_valid,
+            Policy::Positional => self.positional_valid,
+        };
+        valid as f64 / self.total as f64
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+const BAR_WIDTH: usize = 40;
+
+fn write_histogram<K: fmt::Display>(
+    f: &mut fmt::Formatter,
+    title: &str,
+    rows: &BTreeMap<K, usize>,
+) -> fmt::Result {
+    let max = rows.values().copied().max().unwrap_or(0).max(1);
+    writeln!(f, "\n{}", title)?;
+    for (key, count) in rows {
+        // Round up so every non-empty row gets at least one #.
+        let bar = (count * BAR_WIDTH).div_ceil(max);
+        writeln!(f, "{:>8} {:>7} {}", key, count, "#".repeat(bar))?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Passwords: {}", self.total)?;
+        writeln!(f, "\n{:<12} {:>7} {:>9}", "Policy", "Valid", "Pass rate")?;
+        for &(name, policy, valid) in [
+            ("count", Policy::Count, self.count_valid),
+            ("positional", Policy::Positional, self.positional_valid),
+        ]
+        .iter()
+        {
+            writeln!(
+                f,
+                "{:<12} {:>7} {:>8.1}%",
+                name,
+                valid,
+                self.pass_rate(policy) * 100.0
+            )?;
+        }
+
+        writeln!(f, "\n{:<16} {:>7}", "Verdicts", "Count")?;
+        for &(name, count) in [
+            ("both valid", self.agreement.both_valid),
+            ("count only", self.agreement.count_only),
+            ("positional only", self.agreement.positional_only),
+            ("neither valid", self.agreement.neither_valid),
+            ("disagree", self.agreement.disagreements()),
+        ]
+        .iter()
+        {
+            writeln!(f, "{:<16} {:>7}", name, count)?;
+        }
+
+        write_histogram(f, "Policy letters", &self.policy_letters)?;
+        write_histogram(f, "Password characters", &self.password_chars)?;
+        write_histogram(f, "Range widths (hi - lo)", &self.range_widths)?;
+        write_histogram(f, "Password lengths", &self.password_lengths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_file, parse_passwords, part1, part2};
+    use std::path::Path;
+
+    #[test]
+    fn test_stats() {
+        let passwords = parse_passwords("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-2 a: ab");
+        let stats = Stats::new(&passwords);
+
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.policy_letters[&'a'], 2);
+        assert_eq!(stats.password_chars[&'c'], 11);
+        assert_eq!(stats.range_widths.get(&2), Some(&2));
+        assert_eq!(stats.password_lengths.get(&5), Some(&2));
+        assert_eq!(
+            stats.agreement,
+            Agreement {
+                both_valid: 2,
+                count_only: 1,
+                positional_only: 0,
+                neither_valid: 1,
+            }
+        );
+        assert_eq!(stats.agreement.disagreements(), 1);
+        assert_eq!(stats.pass_rate(Policy::Count), 0.75);
+
+        let report = stats.to_string();
+        assert!(report.contains("count              3     75.0%"));
+        assert!(report.contains("disagree               1"));
+        assert!(report.contains("       c      11 ########################################"));
+    }
+
+    #[test]
+    fn test_extreme_range_width() {
+        let stats = Stats::new(&parse_passwords("-2147483648-2147483647 a: abc"));
+        assert_eq!(stats.range_widths.get(&(u32::MAX as i64)), Some(&1));
+    }
+
+    #[test]
+    fn test_stats_match_parts() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let stats = Stats::new(&parse_passwords(&input));
+        assert_eq!(stats.count_valid as i32, part1(&input));
+        assert_eq!(stats.positional_valid as i32, part2(&input));
+        assert_eq!(stats.password_lengths.values().sum::<usize>(), stats.total);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_stats_json() {
+        let stats = Stats::new(&parse_passwords("1-3 a: abcde"));
+        let json: serde_json::Value = serde_json::from_str(&stats.to_json().unwrap()).unwrap();
+        assert_eq!(json["total"], 1);
+        assert_eq!(json["policy_letters"]["a"], 1);
+        assert_eq!(json["agreement"]["both_valid"], 1);
+    }
+}
@@ -0,0 +1,113 @@
+// Validates password databases straight from a reader, for dumps too big to load into memory.
+//
+// Lines are read into a fixed number of reusable buffers, each full chunk is validated in
+// parallel with rayon, and then the buffers are reused for the next chunk, so memory stays at
+// roughly `chunk_lines` lines no matter how big the input is.
+
+use crate::borrowed::parse_line;
+use crate::Policy;
+use rayon::prelude::*;
+use std::io::{self, BufRead};
+
+pub const DEFAULT_CHUNK_LINES: usize = 64 * 1024;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Counts {
+    pub part1: u64,
+    pub part2: u64,
+}
+
+// Reads up to `chunk.len()` lines into the existing buffers, returning how many were read.
+fn read_chunk<R: BufRead>(reader: &mut R, chunk: &mut [String]) -> io::Result<usize> {
+    for (read, line) in chunk.iter_mut().enumerate() {
+        line.clear();
+        if reader.read_line(line)? == 0 {
+            return Ok(read);
+        }
+    }
+    Ok(chunk.len())
+}
+
+// Counts the lines passing each policy, giving the same answers as `part1` and `part2`.
+// Malformed lines are reported as an `InvalidData` error with their 1-based line number.
+pub fn count_valid<R: BufRead>(mut reader: R, chunk_lines: usize) -> io::Result<Counts> {
+    let mut chunk = vec![String::new(); chunk_lines.max(1)];
+    let mut counts = Counts::default();
+    let mut lines_read = 0;
+
+    loop {
+        let read = read_chunk(&mut reader, &mut chunk)?;
+        if read == 0 {
+            return Ok(counts);
+        }
+
+        let chunk_counts = chunk[..read]
+            .par_iter()
+            .enumerate()
+            .map(|(i, line)| -> io::Result<Counts> {
+                let line = line.trim_end_matches(&['\n', '\r'][..]);
+                let pword = parse_line(line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "malformed password on line {}: {:?}",
+                            lines_read + i + 1,
+                            line
+                        ),
+                    )
+                })?;
+                Ok(Counts {
+                    part1: pword.is_valid(Policy::Count) as u64,
+                    part2: pword.is_valid(Policy::Positional) as u64,
+                })
+            })
+            .try_reduce(Counts::default, |a, b| {
+                Ok(Counts {
+                    part1: a.part1 + b.part1,
+                    part2: a.part2 + b.part2,
+                })
+            })?;
+
+        counts.part1 += chunk_counts.part1;
+        counts.part2 += chunk_counts.part2;
+        lines_read += read;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{generate_database, to_input};
+    use crate::{load_file, part1, part2};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::path::Path;
+
+    #[test]
+    fn test_count_valid_matches_parts() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        // A small chunk size so the input is split over several chunks.
+        let counts = count_valid(input.as_bytes(), 7).unwrap();
+        assert_eq!(counts.part1, part1(&input) as u64);
+        assert_eq!(counts.part2, part2(&input) as u64);
+        assert_eq!(
+            count_valid(input.as_bytes(), DEFAULT_CHUNK_LINES).unwrap(),
+            counts
+        );
+    }
+
+    #[test]
+    fn test_count_valid_crlf() {
+        let mut rng = StdRng::seed_from_u64(31);
+        let input = to_input(&generate_database(&mut rng, crate::Policy::Count, 40, 60));
+        let counts = count_valid(input.replace('\n', "\r\n").as_bytes(), 16).unwrap();
+        assert_eq!(counts.part1, 40);
+    }
+
+    #[test]
+    fn test_count_valid_reports_line() {
+        let err = count_valid("1-3 a: abcde\n1-3 b: cdefg\n2-9 c\n".as_bytes(), 2).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 3"));
+    }
+}
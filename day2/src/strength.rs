@@ -0,0 +1,224 @@
+// Password strength estimate, and a policy that only lets through passwords that are strong enough.
+//
+// The estimate is the classic `length * log2(pool size)`, where the pool is the union of the
+// character classes the password uses. Characters that are easy to guess from the ones before
+// them (repeats like "aaa", runs like "abc" or "321", and copies of an earlier chunk like the
+// second half of "abcabc") only count for a quarter of a character.
+
+use crate::{parse_passwords, Password};
+
+const LOWER_POOL: u32 = 26;
+const UPPER_POOL: u32 = 26;
+const DIGIT_POOL: u32 = 10;
+// Printable ascii punctuation and space.
+const SYMBOL_POOL: u32 = 33;
+// Rough guess for everything outside ascii.
+const OTHER_POOL: u32 = 100;
+
+const PREDICTABLE_WEIGHT: f64 = 0.25;
+// Shortest chunk that counts as a copy of an earlier part of the password.
+const MIN_REPEAT: usize = 3;
+
+fn pool_size(chars: &[char]) -> u32 {
+    let has = |f: fn(&char) -> bool| chars.iter().any(f);
+    let mut pool = 0;
+    if has(char::is_ascii_lowercase) {
+        pool += LOWER_POOL;
+    }
+    if has(char::is_ascii_uppercase) {
+        pool += UPPER_POOL;
+    }
+    if has(char::is_ascii_digit) {
+        pool += DIGIT_POOL;
+    }
+    if has(|c| c.is_ascii_punctuation() || *c == ' ') {
+        pool += SYMBOL_POOL;
+    }
+    if has(|c| !c.is_ascii()) {
+        pool += OTHER_POOL;
+    }
+    pool
+}
+
+// How far back to look for a copy, and how long a copy can be in one go. A longer copy is just
+// found again in pieces, so this only bounds the work on long passwords.
+const MAX_LOOKBACK: usize = 256;
+
+// The Z-function: `z[k]` is the length of the longest common prefix of `s` and `s[k..]`.
+fn z_function<T: PartialEq>(s: &[T]) -> Vec<usize> {
+    let mut z = vec![0; s.len()];
+    let (mut left, mut right) = (0, 0);
+    for k in 1..s.len() {
+        if k < right {
+            z[k] = z[k - left].min(right - k);
+        }
+        while k + z[k] < s.len() && s[z[k]] == s[k + z[k]] {
+            z[k] += 1;
+        }
+        if k + z[k] > right {
+            left = k;
+            right = k + z[k];
+        }
+    }
+    z
+}
+
+// Length of the longest chunk starting at `i` that also appears earlier in the password, ending
+// by `i`.
+fn repeat_len(chars: &[char], i: usize) -> usize {
+    let earlier = &chars[i.saturating_sub(MAX_LOOKBACK)..i];
+    let ahead = &chars[i..chars.len().min(i + MAX_LOOKBACK)];
+    // What's ahead, a separator that matches nothing, then what came before. A match starting in
+    // the earlier part runs out at `i`, so it's always a copy of something already seen.
+    let text: Vec<Option<char>> = ahead
+        .iter()
+        .copied()
+        .map(Some)
+        .chain(Some(None))
+        .chain(earlier.iter().copied().map(Some))
+        .collect();
+    let z = z_function(&text);
+    z[ahead.len() + 1..].iter().copied().max().unwrap_or(0)
+}
+
+// Characters worth of entropy, after discounting the predictable ones.
+fn effective_len(chars: &[char]) -> f64 {
+    let mut len = 0.0;
+    let mut i = 0;
+    while i < chars.len() {
+        let repeat = repeat_len(chars, i);
+        if repeat >= MIN_REPEAT {
+            len += repeat as f64 * PREDICTABLE_WEIGHT;
+            i += repeat;
+            continue;
+        }
+
+        let step = |a: usize, b: usize| chars[b] as i64 - chars[a] as i64;
+        let predictable = i >= 1 && step(i - 1, i) == 0
+            || i >= 2 && step(i - 1, i).abs() == 1 && step(i - 2, i - 1) == step(i - 1, i);
+        len += if predictable { PREDICTABLE_WEIGHT } else { 1.0 };
+        i += 1;
+    }
+    len
+}
+
+// Estimated entropy of `password` in bits.
+pub fn entropy_bits(password: &str) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    let pool = pool_size(&chars);
+    if pool == 0 {
+        return 0.0;
+    }
+    effective_len(&chars) * (pool as f64).log2()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrengthPolicy {
+    pub min_bits: f64,
+}
+
+impl StrengthPolicy {
+    pub fn is_valid(&self, pword: &Password) -> bool {
+        entropy_bits(&pword.password) >= self.min_bits
+    }
+}
+
+// Get the number of passwords strong enough for `policy`, like `part1` and `part2`.
+pub fn count_valid(input: &str, policy: StrengthPolicy) -> i32 {
+    let mut valid_passwords = 0;
+    let passwords = parse_passwords(input);
+
+    for pword in passwords.iter() {
+        if policy.is_valid(pword) {
+            valid_passwords += 1;
+        }
+    }
+
+    valid_passwords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use test::Bencher;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_entropy_bits() {
+        assert_close(entropy_bits(""), 0.0);
+        assert_close(entropy_bits("qzmx"), 4.0 * 26_f64.log2());
+        assert_close(entropy_bits("qZ7!"), 4.0 * 95_f64.log2());
+        // "aaaa" is one real character and three repeats.
+        assert_close(entropy_bits("aaaa"), 1.75 * 26_f64.log2());
+        // Runs up or down only count the first two characters fully.
+        assert_close(entropy_bits("abcd"), 2.5 * 26_f64.log2());
+        assert_close(entropy_bits("9876"), 2.5 * 10_f64.log2());
+        // The second "qzm" is a copy of the first.
+        assert_close(entropy_bits("qzmqzm"), 3.75 * 26_f64.log2());
+    }
+
+    #[test]
+    fn test_long_repeats() {
+        // Copies longer than the look-back are found in pieces, and still all discounted.
+        let mut next = crate::tests::lcg();
+        let chunk: Vec<char> = (0..MAX_LOOKBACK)
+            .map(|_| (b'a' + (next() % 26) as u8) as char)
+            .collect();
+        let chars = chunk.repeat(4);
+        assert_eq!(repeat_len(&chars, MAX_LOOKBACK), MAX_LOOKBACK);
+        assert_close(
+            effective_len(&chars),
+            effective_len(&chunk) + 3.0 * MAX_LOOKBACK as f64 * PREDICTABLE_WEIGHT,
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_repeat_len_matches_brute_force(password in "[abc]{0,40}") {
+            let chars: Vec<char> = password.chars().collect();
+            for i in 0..chars.len() {
+                let brute = (0..i)
+                    .map(|start| {
+                        chars[start..i]
+                            .iter()
+                            .zip(&chars[i..])
+                            .take_while(|(a, b)| a == b)
+                            .count()
+                    })
+                    .max()
+                    .unwrap_or(0);
+                prop_assert_eq!(repeat_len(&chars, i), brute);
+            }
+        }
+    }
+
+    #[test]
+    fn test_strength_ranking() {
+        assert!(entropy_bits("password") < entropy_bits("Tr0ub4dor&3"));
+        assert!(entropy_bits("aaaaaaaaaaaa") < entropy_bits("kqzvnwpm"));
+        assert!(entropy_bits("abcabcabcabc") < entropy_bits("abcxyzkqmwvt"));
+    }
+
+    #[test]
+    fn test_count_valid() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-2 x: Xk9$pLm2!q";
+        assert_eq!(count_valid(input, StrengthPolicy { min_bits: 0.0 }), 4);
+        assert_eq!(count_valid(input, StrengthPolicy { min_bits: 20.0 }), 1);
+        assert_eq!(count_valid(input, StrengthPolicy { min_bits: 100.0 }), 0);
+    }
+
+    #[bench]
+    fn bench_entropy_long_password(b: &mut Bencher) {
+        // 16k random lowercase letters, where three letter copies are rare, so nearly every
+        // position is searched.
+        let mut next = crate::tests::lcg();
+        let password: String = (0..16 * 1024)
+            .map(|_| (b'a' + (next() % 26) as u8) as char)
+            .collect();
+        b.iter(|| entropy_bits(test::black_box(&password)))
+    }
+}
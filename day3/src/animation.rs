@@ -0,0 +1,503 @@
+// An animated GIF of the toboggan going down one slope.
+//
+// Each frame moves the toboggan one step along `trajectory`, with a viewport that follows it
+// across the repeating map. Squares it has been through stay marked with the path colours from
+// `ImageOptions`, a tree is highlighted the moment it's hit, and a strip along the top counts the
+// trees hit so far. Everything is drawn straight into a small fixed palette, so there's no
+// colour quantizing to do.
+
+use crate::image::{self, Colour, ExportError, ImageOptions};
+use crate::trajectory::{trajectory, Point};
+use crate::{Grid, Obstacles};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationOptions {
+    pub fps: u16,
+    // Size of the viewport in squares. It's cut down to the map's height if that's smaller.
+    pub columns: usize,
+    pub rows: usize,
+    // Cell size and colours for the map and path.
+    pub image: ImageOptions,
+    pub toboggan: Colour,
+    // The hit counter strip.
+    pub header: Colour,
+    pub text: Colour,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            fps: 10,
+            columns: 48,
+            rows: 32,
+            image: ImageOptions::default(),
+            toboggan: [30, 30, 200],
+            header: [20, 20, 20],
+            text: [255, 255, 255],
+        }
+    }
+}
+
+// Palette indices.
+const EMPTY: u8 = 0;
+const TREE: u8 = 1;
+const PATH_OPEN: u8 = 2;
+const PATH_TREE: u8 = 3;
+const TOBOGGAN: u8 = 4;
+const HEADER: u8 = 5;
+const TEXT: u8 = 6;
+
+// 3x5 digits, a row per byte with the leftmost pixel in the highest of the three bits.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+// A frame as palette indices, row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    pub trees_hit: usize,
+}
+
+impl Frame {
+    fn fill(&mut self, x: u32, y: u32, width: u32, height: u32, index: u8) {
+        for py in y..y.saturating_add(height).min(self.height) {
+            let row = py as usize * self.width as usize;
+            let start = row + x.min(self.width) as usize;
+            let end = row + x.saturating_add(width).min(self.width) as usize;
+            self.pixels[start..end].fill(index);
+        }
+    }
+
+    fn draw_number(&mut self, n: usize, x: u32, y: u32, scale: u32) {
+        for (i, digit) in n.to_string().bytes().enumerate() {
+            let left = x + i as u32 * 4 * scale;
+            for (dy, bits) in DIGITS[(digit - b'0') as usize].iter().enumerate() {
+                for dx in 0..3 {
+                    if bits >> (2 - dx) & 1 == 1 {
+                        let (px, py) = (left + dx * scale, y + dy as u32 * scale);
+                        self.fill(px, py, scale, scale, TEXT);
+                    }
+                }
+            }
+        }
+    }
+
+    // Moves everything below the counter strip up by `dy` pixels and left by `dx`, for when the
+    // viewport scrolls. What's uncovered along the right and bottom is left to be drawn over.
+    fn scroll(&mut self, dx: u32, dy: u32, header: u32) {
+        let width = self.width as usize;
+        let (dx, dy) = (dx as usize, dy as usize);
+        for py in header as usize..self.height as usize - dy {
+            let from = (py + dy) * width + dx;
+            self.pixels.copy_within(from..from + width - dx, py * width);
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> u8 {
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+}
+
+fn palette(options: &AnimationOptions) -> Vec<u8> {
+    let path = options.image.paths.first().copied().unwrap_or_default();
+    [
+        options.image.empty,
+        options.image.tree,
+        path.open,
+        path.tree,
+        options.toboggan,
+        options.header,
+        options.text,
+    ]
+    .concat()
+}
+
+// Height of the counter strip, with a digit's height of padding above and below.
+fn header_height(scale: u32) -> u32 {
+    scale.saturating_mul(7)
+}
+
+// The viewport in squares, the square size in pixels, and the scale of the counter's digits.
+fn viewport(map: &Grid<Obstacles>, options: &AnimationOptions) -> (usize, usize, u32, u32) {
+    let columns = options.columns.max(1);
+    let rows = options.rows.min(map.height()).max(1);
+    let size = options.image.cell_size.max(1);
+    (columns, rows, size, (size / 2).max(1))
+}
+
+// Width and height of every frame in pixels. This is checked up front, so a huge viewport or cell
+// size is an error before any frames are drawn.
+pub fn frame_size(
+    map: &Grid<Obstacles>,
+    options: &AnimationOptions,
+) -> Result<(u32, u32), ExportError> {
+    let (columns, rows, size, scale) = viewport(map, options);
+    let width = (columns as u64).saturating_mul(size as u64);
+    let height = (rows as u64)
+        .saturating_mul(size as u64)
+        .saturating_add(header_height(scale) as u64);
+    let (width, height, _) = image::check_size(width, height, 1)?;
+    Ok((width, height))
+}
+
+// The frames in order, drawn one at a time as they're needed.
+pub fn frames<'a>(
+    map: &'a Grid<Obstacles>,
+    run: usize,
+    rise: usize,
+    options: &AnimationOptions,
+) -> Result<impl Iterator<Item = Frame> + 'a, ExportError> {
+    let (width, height) = frame_size(map, options)?;
+    let (columns, rows, size, scale) = viewport(map, options);
+    let header = header_height(scale);
+    let points: Vec<Point> = trajectory(map, run, rise).collect();
+
+    // Square `(vx, vy)` of the viewport.
+    let square = move |frame: &mut Frame, vx: usize, vy: usize, index: u8| {
+        frame.fill(
+            vx as u32 * size,
+            vy as u32 * size + header,
+            size,
+            size,
+            index,
+        );
+    };
+    // The map's own squares in a part of the viewport, with no path on them.
+    let background = move |frame: &mut Frame, left: usize, top: usize, vxs: Range<usize>, vys| {
+        for vy in vys {
+            for vx in vxs.clone() {
+                let tree = map[(left + vx, top + vy)] == Obstacles::Tree;
+                square(frame, vx, vy, if tree { TREE } else { EMPTY });
+            }
+        }
+    };
+
+    // Each frame is drawn from a copy of the one before, with its viewport's top left.
+    let mut previous: Option<(Frame, usize, usize)> = None;
+    let mut trees_hit = 0;
+    Ok((0..points.len()).map(move |i| {
+        let (x, y, cell) = points[i];
+        if cell == Obstacles::Tree {
+            trees_hit += 1;
+        }
+
+        // Keep the toboggan a third of the way in, so there's more to see ahead of it.
+        let left = x.saturating_sub(columns / 3);
+        let top = y.saturating_sub(rows / 3).min(map.height() - rows);
+        // The toboggan only goes right and down, so the viewport does too. Anything it scrolls
+        // into view is ahead of the toboggan, and so has no path on it yet.
+        let scroll = previous
+            .as_ref()
+            .map(|&(_, prev_left, prev_top)| (left - prev_left, top - prev_top))
+            .filter(|&(dx, dy)| dx < columns && dy < rows);
+        let mut frame = match (previous.take(), scroll) {
+            (Some((mut frame, _, _)), Some((dx, dy))) => {
+                frame.scroll(dx as u32 * size, dy as u32 * size, header);
+                background(&mut frame, left, top, columns - dx..columns, 0..rows);
+                background(&mut frame, left, top, 0..columns - dx, rows - dy..rows);
+                // Where the toboggan was last frame becomes part of the path.
+                let (px, py, last) = points[i - 1];
+                if last == Obstacles::Empty && px >= left && py >= top {
+                    square(&mut frame, px - left, py - top, PATH_OPEN);
+                }
+                frame
+            }
+            // The first frame, or a jump so big that nothing on the path is still in view.
+            _ => {
+                let mut frame = Frame {
+                    width,
+                    height,
+                    pixels: vec![EMPTY; width as usize * height as usize],
+                    trees_hit,
+                };
+                background(&mut frame, left, top, 0..columns, 0..rows);
+                frame
+            }
+        };
+        frame.trees_hit = trees_hit;
+        // Trees light up as they're hit, and stay lit.
+        let index = match cell {
+            Obstacles::Tree => PATH_TREE,
+            Obstacles::Empty => TOBOGGAN,
+        };
+        square(&mut frame, x - left, y - top, index);
+
+        frame.fill(0, 0, width, header, HEADER);
+        // A tree, then the count.
+        frame.fill(scale, scale, 3 * scale, 5 * scale, TREE);
+        frame.draw_number(trees_hit, 5 * scale, scale, scale);
+        previous = Some((frame.clone(), left, top));
+        frame
+    }))
+}
+
+// GIF frame delays are whole hundredths of a second, and browsers play anything under 2 of them
+// at a tenth of a second, so the fastest GIF that plays as asked is 50 fps.
+pub const MAX_FPS: u16 = 50;
+
+// The frame delay in hundredths of a second closest to `fps`, which is clamped to 1..=MAX_FPS.
+// The GIF plays at `100 / delay` fps, which is only `fps` when it divides 100.
+pub fn frame_delay(fps: u16) -> u16 {
+    let fps = fps.clamp(1, MAX_FPS);
+    (100 + fps / 2) / fps
+}
+
+// Everything that can stop a GIF being made, checked before anything's written. Gives back the
+// size of the frames.
+fn check_gif(map: &Grid<Obstacles>, options: &AnimationOptions) -> Result<(u16, u16), ExportError> {
+    if options.fps == 0 {
+        return Err(ExportError::ZeroFrameRate);
+    }
+    // There has to be at least one frame.
+    if map.height() == 0 {
+        return Err(ExportError::EmptyMap);
+    }
+    // GIFs can't be more than 65535 pixels either way.
+    let (width, height) = frame_size(map, options)?;
+    let too_large = || ExportError::TooLarge {
+        width: width as u64,
+        height: height as u64,
+    };
+    let width = u16::try_from(width).map_err(|_| too_large())?;
+    let height = u16::try_from(height).map_err(|_| too_large())?;
+    Ok((width, height))
+}
+
+pub fn write_gif<W: Write>(
+    out: W,
+    map: &Grid<Obstacles>,
+    run: usize,
+    rise: usize,
+    options: &AnimationOptions,
+) -> Result<(), ExportError> {
+    let (width, height) = check_gif(map, options)?;
+    let frames = frames(map, run, rise, options)?;
+    let mut encoder = gif::Encoder::new(out, width, height, &palette(options))?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    let delay = frame_delay(options.fps);
+    for frame in frames {
+        encoder.write_frame(&gif::Frame {
+            width,
+            height,
+            delay,
+            buffer: Cow::Borrowed(&frame.pixels),
+            ..gif::Frame::default()
+        })?;
+    }
+    Ok(())
+}
+
+pub fn save_gif(
+    path: &Path,
+    map: &Grid<Obstacles>,
+    run: usize,
+    rise: usize,
+    options: &AnimationOptions,
+) -> Result<(), ExportError> {
+    // So a GIF that can't be made doesn't leave an empty file behind.
+    check_gif(map, options)?;
+    let mut out = BufWriter::new(File::create(path)?);
+    write_gif(&mut out, map, run, rise, options)?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_trees_hit, parse_map};
+
+    const EXAMPLE: &str = "..##.......\n\
+        #...#...#..\n\
+        .#....#..#.\n\
+        ..#.#...#.#\n\
+        .#...##..#.\n\
+        ..#.##.....\n\
+        .#.#.#....#\n\
+        .#........#\n\
+        #.##...#...\n\
+        #...##....#\n\
+        .#..#...#.#";
+
+    fn small() -> AnimationOptions {
+        AnimationOptions {
+            columns: 8,
+            rows: 4,
+            image: ImageOptions {
+                cell_size: 2,
+                ..ImageOptions::default()
+            },
+            ..AnimationOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_frames() {
+        let map = parse_map(EXAMPLE).unwrap();
+        let options = small();
+        let frames: Vec<Frame> = frames(&map, 3, 1, &options).unwrap().collect();
+        assert_eq!(frames.len(), 11);
+        assert!(frames
+            .iter()
+            .all(|frame| (frame.width, frame.height) == (16, 15)));
+        let counts: Vec<usize> = frames.iter().map(|frame| frame.trees_hit).collect();
+        assert_eq!(counts, vec![0, 0, 1, 1, 2, 3, 3, 4, 5, 6, 7]);
+        assert_eq!(*counts.last().unwrap() as i64, get_trees_hit(&map, 3, 1));
+
+        // At the start the toboggan is in the top left, under the counter strip.
+        let header = header_height(1);
+        assert_eq!(frames[0].pixel(0, header), TOBOGGAN);
+        assert_eq!(frames[0].pixel(0, 0), HEADER);
+        // Step 2 hits the tree at (6, 2), which the viewport keeps 2 columns in and 1 row down.
+        assert_eq!(frames[2].pixel(2 * 2, header + 2), PATH_TREE);
+    }
+
+    #[test]
+    fn test_frames_match_redrawing() {
+        // Frames are drawn from the one before, so check every square against the whole path.
+        let map = parse_map(EXAMPLE).unwrap();
+        let options = small();
+        let header = header_height(1);
+        // Slopes that scroll a little at a time, and ones that jump past the whole viewport.
+        for &(run, rise) in &[(3, 1), (1, 2), (0, 1), (9, 1), (1, 5)] {
+            let points: Vec<Point> = trajectory(&map, run, rise).collect();
+            for (i, frame) in frames(&map, run, rise, &options).unwrap().enumerate() {
+                let (x, y, _) = points[i];
+                let left = x.saturating_sub(8 / 3);
+                let top = y.saturating_sub(4 / 3).min(map.height() - 4);
+                for vy in 0..4 {
+                    for vx in 0..8 {
+                        let (mx, my) = (left + vx, top + vy);
+                        let path = points[..=i].iter().find(|p| (p.0, p.1) == (mx, my));
+                        let expected = match path {
+                            Some((_, _, Obstacles::Tree)) => PATH_TREE,
+                            Some(_) if (mx, my) == (x, y) => TOBOGGAN,
+                            Some(_) => PATH_OPEN,
+                            None if map[(mx, my)] == Obstacles::Tree => TREE,
+                            None => EMPTY,
+                        };
+                        let pixel = frame.pixel(vx as u32 * 2, vy as u32 * 2 + header);
+                        assert_eq!(pixel, expected, "slope {:?}, frame {}", (run, rise), i);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_digits() {
+        let mut frame = Frame {
+            width: 8,
+            height: 5,
+            pixels: vec![EMPTY; 40],
+            trees_hit: 0,
+        };
+        frame.draw_number(17, 0, 0, 1);
+        let row = |y: u32| {
+            (0..8)
+                .map(|x| frame.pixel(x, y) == TEXT)
+                .collect::<Vec<_>>()
+        };
+        // "1" is .#. on top and ### at the bottom, "7" is ### then .#.
+        assert_eq!(
+            row(0),
+            vec![false, true, false, false, true, true, true, false]
+        );
+        assert_eq!(
+            row(4),
+            vec![true, true, true, false, false, true, false, false]
+        );
+    }
+
+    #[test]
+    fn test_gif() {
+        let map = parse_map(EXAMPLE).unwrap();
+        let options = small();
+        let mut gif = Vec::new();
+        write_gif(&mut gif, &map, 3, 1, &options).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new();
+        decoder.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = decoder.read_info(&gif[..]).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (16, 15));
+        let expected: Vec<Frame> = frames(&map, 3, 1, &options).unwrap().collect();
+        let mut count = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            assert_eq!(&frame.buffer[..], &expected[count].pixels[..]);
+            count += 1;
+        }
+        assert_eq!(count, 11);
+    }
+
+    #[test]
+    fn test_frame_delay() {
+        assert_eq!(frame_delay(10), 10);
+        assert_eq!(frame_delay(1), 100);
+        // 30 fps rounds to 3/100 s, which is really 33.3 fps.
+        assert_eq!(frame_delay(30), 3);
+        assert_eq!(frame_delay(40), 3);
+        assert_eq!(frame_delay(50), 2);
+        assert_eq!(frame_delay(100), 2);
+    }
+
+    #[test]
+    fn test_errors_before_drawing() {
+        let map = parse_map(EXAMPLE).unwrap();
+        let write = |options: &AnimationOptions| write_gif(Vec::new(), &map, 3, 1, options);
+        // 40_000 columns of 2 pixels is fine for a picture, but too wide for a GIF.
+        let wide = AnimationOptions {
+            columns: 40_000,
+            ..small()
+        };
+        assert!(frames(&map, 3, 1, &wide).is_ok());
+        assert!(matches!(
+            write(&wide),
+            Err(ExportError::TooLarge {
+                width: 80_000,
+                height: 15
+            })
+        ));
+
+        let huge = AnimationOptions {
+            image: ImageOptions {
+                cell_size: u32::MAX,
+                ..ImageOptions::default()
+            },
+            ..small()
+        };
+        assert!(matches!(
+            frames(&map, 3, 1, &huge),
+            Err(ExportError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            write(&AnimationOptions { fps: 0, ..small() }),
+            Err(ExportError::ZeroFrameRate)
+        ));
+
+        let path = std::env::temp_dir().join("day3-animation-test-empty.gif");
+        assert!(matches!(
+            save_gif(&path, &parse_map("").unwrap(), 3, 1, &small()),
+            Err(ExportError::EmptyMap)
+        ));
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,278 @@
+// The map packed into bits, one `u64` word for every 64 columns of a row, with a set bit for a
+// tree. That's an eighth of the memory of `Grid<Obstacles>`, and a whole row of the puzzle input
+// fits in one word.
+
+use crate::grid::{self, ParseGridError, ParseOptions};
+use crate::{Grid, Obstacles};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitMap {
+    words: Vec<u64>,
+    words_per_row: usize,
+    width: usize,
+    height: usize,
+}
+
+impl BitMap {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn from_grid(grid: &Grid<Obstacles>) -> BitMap {
+        let words_per_row = grid.width().div_ceil(64);
+        let mut words = vec![0; words_per_row * grid.height()];
+        for (y, row) in grid.rows().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if cell == Obstacles::Tree {
+                    words[y * words_per_row + x / 64] |= 1 << (x % 64);
+                }
+            }
+        }
+        BitMap {
+            words,
+            words_per_row,
+            width: grid.width(),
+            height: grid.height(),
+        }
+    }
+
+    // Parses the same `#`/`.` format as `Grid`, with the same errors, but a byte at a time
+    // straight into words.
+    pub fn parse_with(input: &str, options: ParseOptions) -> Result<BitMap, ParseGridError> {
+        let lines = grid::rows(input, options);
+        let width = lines.first().map_or(0, |line| line.len());
+        let words_per_row = width.div_ceil(64);
+        let mut words = Vec::with_capacity(words_per_row * lines.len());
+
+        for (y, line) in lines.iter().enumerate() {
+            for chunk in line.as_bytes().chunks(64) {
+                let mut word = 0;
+                let mut invalid = false;
+                for (i, &b) in chunk.iter().enumerate() {
+                    word |= ((b == b'#') as u64) << i;
+                    invalid |= b != b'#' && b != b'.';
+                }
+                if invalid {
+                    return Err(invalid_cell(y, line));
+                }
+                words.push(word);
+            }
+            // Everything valid is a byte wide, so the byte length is the number of cells.
+            if line.len() != width {
+                return Err(ParseGridError::RaggedRow {
+                    line: y + 1,
+                    expected: width,
+                    actual: line.len(),
+                });
+            }
+        }
+
+        Ok(BitMap {
+            words,
+            words_per_row,
+            width,
+            // Like `Grid`, an empty line on its own is an empty map rather than a row of nothing.
+            height: if width == 0 { 0 } else { lines.len() },
+        })
+    }
+
+    #[inline(always)]
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    // Whether there's a tree at (`x` wrapped, `y`). Panics past the bottom.
+    #[inline(always)]
+    pub fn is_tree(&self, x: usize, y: usize) -> bool {
+        let x = x % self.width;
+        self.row(y)[x / 64] >> (x % 64) & 1 == 1
+    }
+
+    pub fn trees_in_row(&self, y: usize) -> u32 {
+        self.row(y).iter().map(|word| word.count_ones()).sum()
+    }
+
+    pub fn trees(&self) -> u64 {
+        self.words.iter().map(|word| word.count_ones() as u64).sum()
+    }
+
+    #[inline(always)]
+    fn bit(row: &[u64], x: usize) -> i64 {
+        (row[x / 64] >> (x % 64) & 1) as i64
+    }
+
+    pub fn trees_hit(&self, run: usize, rise: usize) -> i64 {
+        assert!(rise > 0, "A slope with no rise never reaches the bottom");
+        let run = run % self.width.max(1);
+        let mut x = 0;
+        let mut trees_hit = 0;
+        for y in (0..self.height).step_by(rise) {
+            trees_hit += BitMap::bit(self.row(y), x);
+            // Kept wrapped as we go, so there's no divide per row.
+            x += run;
+            if x >= self.width {
+                x -= self.width;
+            }
+        }
+        trees_hit
+    }
+
+    // Trees hit by each `(run, rise)` slope, all counted in one sweep down the rows. It's the packed
+    // map's version of `search::count_columns`, kept single threaded since a row is one word.
+    pub fn trees_hit_batch(&self, slopes: &[(usize, usize)]) -> Vec<i64> {
+        struct State {
+            run: usize,
+            rise: usize,
+            x: usize,
+            next_row: usize,
+            trees_hit: i64,
+        }
+        let mut states: Vec<State> = slopes
+            .iter()
+            .map(|&(run, rise)| {
+                assert!(rise > 0, "A slope with no rise never reaches the bottom");
+                State {
+                    run: run % self.width.max(1),
+                    rise,
+                    x: 0,
+                    next_row: 0,
+                    trees_hit: 0,
+                }
+            })
+            .collect();
+
+        for y in 0..self.height {
+            let row = self.row(y);
+            for state in states.iter_mut().filter(|state| state.next_row == y) {
+                state.trees_hit += BitMap::bit(row, state.x);
+                state.next_row += state.rise;
+                state.x += state.run;
+                if state.x >= self.width {
+                    state.x -= self.width;
+                }
+            }
+        }
+        states.iter().map(|state| state.trees_hit).collect()
+    }
+}
+
+// Finds the first character that isn't `#` or `.` in a line the byte scan rejected.
+fn invalid_cell(y: usize, line: &str) -> ParseGridError {
+    let (x, c) = line
+        .chars()
+        .enumerate()
+        .find(|&(_, c)| c != '#' && c != '.')
+        .unwrap();
+    ParseGridError::InvalidCell {
+        line: y + 1,
+        column: x + 1,
+        c,
+    }
+}
+
+// Parses strictly, like `Grid`.
+impl FromStr for BitMap {
+    type Err = ParseGridError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        BitMap::parse_with(input, ParseOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_trees_hit, load_file, parse_map};
+    use std::path::Path;
+    use test::Bencher;
+
+    const SLOPES: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+    #[test]
+    fn test_matches_grid() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let grid = parse_map(&input).unwrap();
+        let bitmap: BitMap = input.parse().unwrap();
+        assert_eq!(bitmap, BitMap::from_grid(&grid));
+
+        let counts = bitmap.trees_hit_batch(&SLOPES);
+        for (&(run, rise), &count) in SLOPES.iter().zip(&counts) {
+            assert_eq!(bitmap.trees_hit(run, rise), get_trees_hit(&grid, run, rise));
+            assert_eq!(count, get_trees_hit(&grid, run, rise));
+        }
+        let trees = grid
+            .cells()
+            .iter()
+            .filter(|&&cell| cell == Obstacles::Tree)
+            .count();
+        assert_eq!(bitmap.trees(), trees as u64);
+    }
+
+    #[test]
+    fn test_wide_rows() {
+        // 130 columns, so rows span three words with a partial last one.
+        let row: String = (0..130)
+            .map(|x| if x % 7 == 0 { '#' } else { '.' })
+            .collect();
+        let input = format!("{}\n{}", row, row.chars().rev().collect::<String>());
+        let grid = parse_map(&input).unwrap();
+        let bitmap: BitMap = input.parse().unwrap();
+        assert_eq!(bitmap, BitMap::from_grid(&grid));
+        for y in 0..2 {
+            for x in 0..300 {
+                assert_eq!(bitmap.is_tree(x, y), grid[(x, y)] == Obstacles::Tree);
+            }
+        }
+        assert_eq!(bitmap.trees_in_row(0), 19);
+        assert_eq!(
+            bitmap.trees_hit_batch(&[(129, 1), (131, 1)]),
+            vec![get_trees_hit(&grid, 129, 1), get_trees_hit(&grid, 131, 1)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "never reaches the bottom")]
+    fn test_no_rise() {
+        "..#".parse::<BitMap>().unwrap().trees_hit(1, 0);
+    }
+
+    #[test]
+    fn test_same_errors_as_grid() {
+        for input in &["..#\n#.", "..#\n#\u{e9}.", "..#\r\n##.", "..#\n##.\n\n"] {
+            assert_eq!(
+                input.parse::<BitMap>().map(|_| ()),
+                parse_map(input).map(|_| ())
+            );
+        }
+        assert_eq!("".parse::<BitMap>().unwrap().height(), 0);
+        assert_eq!(
+            BitMap::parse_with("..#\r\n##.\r\n\r\n", ParseOptions::lenient()),
+            "..#\n##.".parse()
+        );
+    }
+
+    #[bench]
+    fn bench_bitmap_parse(b: &mut Bencher) {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        b.iter(|| input.parse::<BitMap>().unwrap())
+    }
+
+    #[bench]
+    fn bench_bitmap_trees_hit(b: &mut Bencher) {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let bitmap: BitMap = input.parse().unwrap();
+        b.iter(|| bitmap.trees_hit(3, 1))
+    }
+
+    #[bench]
+    fn bench_bitmap_trees_hit_batch(b: &mut Bencher) {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let bitmap: BitMap = input.parse().unwrap();
+        b.iter(|| bitmap.trees_hit_batch(&SLOPES))
+    }
+}
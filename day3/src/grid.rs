@@ -0,0 +1,334 @@
+// A rectangular grid that repeats forever to the right, like the puzzle's map.
+//
+// Cells are stored row by row. `x` wraps around the width everywhere, `y` doesn't, since the
+// toboggan is done once it's off the bottom.
+
+use rayon::prelude::*;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Index;
+use std::slice::ChunksExact;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    // Panics if `cells` doesn't fill a whole number of rows.
+    pub fn new(cells: Vec<T>, width: usize) -> Grid<T> {
+        let height = if width == 0 {
+            assert!(cells.is_empty(), "Can't have cells in a zero width grid");
+            0
+        } else {
+            assert_eq!(
+                cells.len() % width,
+                0,
+                "{} cells isn't a whole number of rows of {}",
+                cells.len(),
+                width
+            );
+            cells.len() / width
+        };
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    // The cell at (`x` wrapped, `y`), or `None` past the bottom.
+    #[inline(always)]
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if y < self.height {
+            Some(&self.cells[y * self.width + x % self.width])
+        } else {
+            None
+        }
+    }
+
+    // Like `get`, but `x` can be negative and wraps leftwards too.
+    #[inline(always)]
+    pub fn get_signed(&self, x: i64, y: usize) -> Option<&T> {
+        self.get(x.rem_euclid(self.width as i64) as usize, y)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> ChunksExact<'_, T> {
+        // `chunks_exact` panics on 0, and an empty grid has no rows anyway.
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    // Column `x` wrapped, top to bottom.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let x = if self.width == 0 { 0 } else { x % self.width };
+        self.rows().map(move |row| &row[x])
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+}
+
+impl<T: Sync> Grid<T> {
+    pub fn par_rows(&self) -> rayon::slice::ChunksExact<'_, T> {
+        self.cells.par_chunks_exact(self.width.max(1))
+    }
+}
+
+// Indexes with (x, y), wrapping x. Panics past the bottom.
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    #[inline(always)]
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y)
+            .unwrap_or_else(|| panic!("Row {} is past the bottom of the grid", y))
+    }
+}
+
+// Writes the grid back out one row per line, the same as it's parsed.
+impl<T: Copy + Into<char>> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for &cell in row {
+                write!(f, "{}", cell.into())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseGridError {
+    // Lines and columns are 1-based, like an editor. The width is set by the first line.
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        actual: usize,
+    },
+    InvalidCell {
+        line: usize,
+        column: usize,
+        c: char,
+    },
+}
+
+impl fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseGridError::RaggedRow {
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "line {}: expected {} cells like the first line, found {}",
+                line, expected, actual
+            ),
+            ParseGridError::InvalidCell {
+                line,
+                column: _,
+                c: '\r',
+            } => write!(
+                f,
+                "line {}: unexpected '\\r', the map has CRLF line endings",
+                line
+            ),
+            ParseGridError::InvalidCell { line, column, c } => {
+                write!(f, "line {}, column {}: unexpected {:?}", line, column, c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseGridError {}
+
+// Things to put up with when parsing. By default neither is allowed, and both are errors.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ParseOptions {
+    // Blank lines after the last row, on top of the usual final newline.
+    pub trailing_empty_lines: bool,
+    // `\r\n` line endings.
+    pub crlf: bool,
+}
+
+impl ParseOptions {
+    pub fn lenient() -> ParseOptions {
+        ParseOptions {
+            trailing_empty_lines: true,
+            crlf: true,
+        }
+    }
+}
+
+// Splits the input into rows, dropping whatever `options` allows.
+pub(crate) fn rows(input: &str, options: ParseOptions) -> Vec<&str> {
+    let input = input.strip_suffix('\n').unwrap_or(input);
+    let mut lines: Vec<&str> = input
+        .split('\n')
+        .map(|line| match line.strip_suffix('\r') {
+            Some(line) if options.crlf => line,
+            _ => line,
+        })
+        .collect();
+    if options.trailing_empty_lines {
+        while lines.len() > 1 && lines.last() == Some(&"") {
+            lines.pop();
+        }
+    }
+    lines
+}
+
+impl<T: TryFrom<char>> Grid<T> {
+    pub fn parse_with(input: &str, options: ParseOptions) -> Result<Grid<T>, ParseGridError> {
+        let lines = rows(input, options);
+        let mut cells = Vec::with_capacity(input.len());
+        let mut width = None;
+        for (y, line) in lines.iter().enumerate() {
+            let start = cells.len();
+            for (x, c) in line.chars().enumerate() {
+                let cell = T::try_from(c).map_err(|_| ParseGridError::InvalidCell {
+                    line: y + 1,
+                    column: x + 1,
+                    c,
+                })?;
+                cells.push(cell);
+            }
+            let actual = cells.len() - start;
+            let expected = *width.get_or_insert(actual);
+            if actual != expected {
+                return Err(ParseGridError::RaggedRow {
+                    line: y + 1,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        Ok(Grid::new(cells, width.unwrap_or(0)))
+    }
+}
+
+// Parses strictly, see `Grid::parse_with` to allow CRLF or trailing blank lines.
+impl<T: TryFrom<char>> FromStr for Grid<T> {
+    type Err = ParseGridError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Grid::parse_with(input, ParseOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Obstacles::{self, Empty, Tree};
+
+    #[test]
+    fn test_wrapping_index() {
+        let grid: Grid<Obstacles> = "..#\n##.".parse().unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 0)], Tree);
+        assert_eq!(grid[(5, 0)], Tree);
+        assert_eq!(grid[(3, 1)], Tree);
+        assert_eq!(grid.get(0, 2), None);
+        assert_eq!(grid.get_signed(-1, 0), Some(&Tree));
+        assert_eq!(grid.get_signed(-4, 1), Some(&Empty));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid: Grid<Obstacles> = "..#\n##.".parse().unwrap();
+        assert_eq!(
+            grid.rows().collect::<Vec<_>>(),
+            vec![grid.row(0), grid.row(1)]
+        );
+        assert_eq!(grid.row(1), &[Tree, Tree, Empty]);
+        assert_eq!(
+            grid.column(4).copied().collect::<Vec<_>>(),
+            vec![Empty, Tree]
+        );
+        assert_eq!(grid.columns().count(), 3);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let input = "..#\n##.";
+        let grid: Grid<Obstacles> = input.parse().unwrap();
+        assert_eq!(grid.to_string(), input);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "..#\n#.\n...".parse::<Grid<Obstacles>>(),
+            Err(ParseGridError::RaggedRow {
+                line: 2,
+                expected: 3,
+                actual: 2
+            })
+        );
+        let err = "..#\n#o.".parse::<Grid<Obstacles>>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseGridError::InvalidCell {
+                line: 2,
+                column: 2,
+                c: 'o'
+            }
+        );
+        assert_eq!(err.to_string(), "line 2, column 2: unexpected 'o'");
+        let empty: Grid<Obstacles> = "".parse().unwrap();
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let input = "..#\r\n##.\r\n\r\n\r\n";
+        let strict = input.parse::<Grid<Obstacles>>();
+        assert_eq!(
+            strict,
+            Err(ParseGridError::InvalidCell {
+                line: 1,
+                column: 4,
+                c: '\r'
+            })
+        );
+        let crlf = ParseOptions {
+            crlf: true,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            Grid::<Obstacles>::parse_with(input, crlf),
+            Err(ParseGridError::RaggedRow {
+                line: 3,
+                expected: 3,
+                actual: 0
+            })
+        );
+        let grid: Grid<Obstacles> = Grid::parse_with(input, ParseOptions::lenient()).unwrap();
+        assert_eq!(grid, "..#\n##.\n".parse().unwrap());
+    }
+}
@@ -0,0 +1,332 @@
+// Pictures of the map and toboggan paths, as PPM or PNG.
+//
+// Each square is a `cell_size` block of pixels, and the map is repeated out the same way as
+// render.rs does for text. PNGs are encoded with the pure Rust `png` crate, so this works
+// anywhere without system libraries.
+
+use crate::render::Overlay;
+use crate::{Grid, Obstacles};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub type Colour = [u8; 3];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PathColours {
+    pub open: Colour,
+    pub tree: Colour,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageOptions {
+    // Width and height of each square in pixels.
+    pub cell_size: u32,
+    pub empty: Colour,
+    pub tree: Colour,
+    // Colours for each slope in turn, going round again if there are more slopes.
+    pub paths: Vec<PathColours>,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        // Snow and trees, with paths in the same order of colours as the terminal markers.
+        let path = |open, tree| PathColours { open, tree };
+        ImageOptions {
+            cell_size: 4,
+            empty: [240, 240, 240],
+            tree: [34, 100, 34],
+            paths: vec![
+                path([120, 220, 120], [200, 30, 30]),
+                path([120, 220, 220], [0, 90, 160]),
+                path([220, 120, 220], [130, 0, 130]),
+                path([230, 220, 100], [170, 110, 0]),
+            ],
+        }
+    }
+}
+
+// The most memory a picture's pixels can take up. Past this a big `cell_size` is almost certainly a
+// mistake, and it's better to say so than to try to allocate it.
+const MAX_BYTES: usize = 1 << 30;
+
+// Checks a `width` by `height` picture at `bytes_per_pixel` fits in `MAX_BYTES`, giving the
+// dimensions back as `u32`s and the length of its buffer.
+pub(crate) fn check_size(
+    width: u64,
+    height: u64,
+    bytes_per_pixel: usize,
+) -> Result<(u32, u32, usize), ExportError> {
+    let too_large = || ExportError::TooLarge { width, height };
+    let width32 = u32::try_from(width).map_err(|_| too_large())?;
+    let height32 = u32::try_from(height).map_err(|_| too_large())?;
+    let len = (width32 as usize)
+        .checked_mul(height32 as usize)
+        .and_then(|pixels| pixels.checked_mul(bytes_per_pixel))
+        .filter(|&len| len <= MAX_BYTES)
+        .ok_or_else(too_large)?;
+    Ok((width32, height32, len))
+}
+
+// 8-bit RGB pixels, row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, background: Colour) -> Result<Image, ExportError> {
+        let (width, height, len) = check_size(width as u64, height as u64, 3)?;
+        Ok(Image {
+            width,
+            height,
+            pixels: background.repeat(len / 3),
+        })
+    }
+
+    #[inline]
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 3
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Colour {
+        let i = self.index(x, y);
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    // Fills the rectangle, clipped to the image.
+    pub fn fill(&mut self, x: u32, y: u32, width: u32, height: u32, colour: Colour) {
+        for py in y..y.saturating_add(height).min(self.height) {
+            for px in x..x.saturating_add(width).min(self.width) {
+                let i = self.index(px, py);
+                self.pixels[i..i + 3].copy_from_slice(&colour);
+            }
+        }
+    }
+
+    // Binary PPM (P6).
+    pub fn write_ppm<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.pixels)
+    }
+
+    pub fn write_png<W: Write>(&self, out: W) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(out, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()
+    }
+
+    // Saves as PPM or PNG depending on the extension.
+    pub fn save(&self, path: &Path) -> Result<(), ExportError> {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        let format = match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("ppm") => Format::Ppm,
+            Some("png") => Format::Png,
+            _ => return Err(ExportError::UnknownFormat(path.display().to_string())),
+        };
+        let mut out = BufWriter::new(File::create(path)?);
+        match format {
+            Format::Ppm => self.write_ppm(&mut out)?,
+            Format::Png => self.write_png(&mut out)?,
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+enum Format {
+    Ppm,
+    Png,
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Io(io::Error),
+    Png(png::EncodingError),
+    Gif(gif::EncodingError),
+    // Only `.ppm` and `.png` are supported.
+    UnknownFormat(String),
+    // More than `MAX_BYTES` of pixels, or more than a GIF's 65535 either way.
+    TooLarge { width: u64, height: u64 },
+    // An animation has to move at least one frame a second.
+    ZeroFrameRate,
+    // An animation of a map with no rows would have no frames.
+    EmptyMap,
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::Io(err) => write!(f, "couldn't write image: {}", err),
+            ExportError::Png(err) => write!(f, "couldn't encode PNG: {}", err),
+            ExportError::Gif(err) => write!(f, "couldn't encode GIF: {}", err),
+            ExportError::UnknownFormat(path) => {
+                write!(f, "{} isn't a .ppm or .png file", path)
+            }
+            ExportError::TooLarge { width, height } => {
+                write!(f, "{}x{} pixels is too large", width, height)
+            }
+            ExportError::ZeroFrameRate => write!(f, "can't animate at 0 frames per second"),
+            ExportError::EmptyMap => write!(f, "can't animate an empty map"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<io::Error> for ExportError {
+    fn from(err: io::Error) -> Self {
+        ExportError::Io(err)
+    }
+}
+
+impl From<gif::EncodingError> for ExportError {
+    fn from(err: gif::EncodingError) -> Self {
+        ExportError::Gif(err)
+    }
+}
+
+impl From<png::EncodingError> for ExportError {
+    fn from(err: png::EncodingError) -> Self {
+        ExportError::Png(err)
+    }
+}
+
+pub fn draw(
+    map: &Grid<Obstacles>,
+    slopes: &[(usize, usize)],
+    options: &ImageOptions,
+) -> Result<Image, ExportError> {
+    let overlay = Overlay::new(map, slopes);
+    let size = options.cell_size;
+    // Checked in u64 first, so a huge `cell_size` is an error rather than an overflow.
+    let (width, height, _) = check_size(
+        (overlay.columns as u64).saturating_mul(size as u64),
+        (map.height() as u64).saturating_mul(size as u64),
+        3,
+    )?;
+    let mut image = Image::new(width, height, options.empty)?;
+    for (y, row) in map.rows().enumerate() {
+        for x in 0..overlay.columns {
+            let cell = row[x % map.width()];
+            let path = overlay
+                .slope_at(x, y)
+                .filter(|_| !options.paths.is_empty())
+                .map(|slope| options.paths[slope % options.paths.len()]);
+            let colour = match (cell, path) {
+                (Obstacles::Empty, None) => continue,
+                (Obstacles::Tree, None) => options.tree,
+                (Obstacles::Empty, Some(path)) => path.open,
+                (Obstacles::Tree, Some(path)) => path.tree,
+            };
+            image.fill(x as u32 * size, y as u32 * size, size, size, colour);
+        }
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_map;
+
+    #[test]
+    fn test_draw() {
+        let map = parse_map(".#\n.#").unwrap();
+        let options = ImageOptions {
+            cell_size: 2,
+            ..ImageOptions::default()
+        };
+        let image = draw(&map, &[(3, 1)], &options).unwrap();
+        // The path ends at x = 3, so the map is drawn twice across.
+        assert_eq!((image.width, image.height), (8, 4));
+        let path = options.paths[0];
+        assert_eq!(image.pixel(1, 1), path.open);
+        assert_eq!(image.pixel(6, 3), path.tree);
+        assert_eq!(image.pixel(2, 0), options.tree);
+        assert_eq!(image.pixel(7, 1), options.tree);
+        assert_eq!(image.pixel(4, 2), options.empty);
+    }
+
+    #[test]
+    fn test_too_large() {
+        let map = parse_map(".#\n.#").unwrap();
+        let options = |cell_size| ImageOptions {
+            cell_size,
+            ..ImageOptions::default()
+        };
+        // 2 * 80_000 squared is bigger than a u32, and 2 * 30_000 squared is too much memory.
+        assert!(matches!(
+            draw(&map, &[(7, 1)], &options(80_000)),
+            Err(ExportError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            draw(&map, &[], &options(30_000)),
+            Err(ExportError::TooLarge {
+                width: 60_000,
+                height: 60_000
+            })
+        ));
+        assert!(matches!(
+            draw(&map, &[], &options(u32::MAX)),
+            Err(ExportError::TooLarge { .. })
+        ));
+        assert!(Image::new(u32::MAX, u32::MAX, [0; 3]).is_err());
+    }
+
+    #[test]
+    fn test_ppm() {
+        let map = parse_map("#.").unwrap();
+        let options = ImageOptions {
+            cell_size: 1,
+            ..ImageOptions::default()
+        };
+        let mut ppm = Vec::new();
+        draw(&map, &[], &options)
+            .unwrap()
+            .write_ppm(&mut ppm)
+            .unwrap();
+        let mut expected = b"P6\n2 1\n255\n".to_vec();
+        expected.extend_from_slice(&options.tree);
+        expected.extend_from_slice(&options.empty);
+        assert_eq!(ppm, expected);
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let map = parse_map("..#\n#..\n.#.").unwrap();
+        let image = draw(&map, &[(1, 1), (2, 1)], &ImageOptions::default()).unwrap();
+        let mut png = Vec::new();
+        image.write_png(&mut png).unwrap();
+
+        let mut reader = png::Decoder::new(&png[..]).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (image.width, image.height));
+        assert_eq!(&pixels[..info.buffer_size()], &image.pixels[..]);
+    }
+
+    #[test]
+    fn test_save() {
+        let map = parse_map("#.").unwrap();
+        let image = draw(&map, &[(1, 1)], &ImageOptions::default()).unwrap();
+        let dir = std::env::temp_dir();
+        for name in &["day3-image-test.ppm", "day3-image-test.png"] {
+            let path = dir.join(name);
+            image.save(&path).unwrap();
+            assert!(std::fs::metadata(&path).unwrap().len() > 0);
+            std::fs::remove_file(&path).unwrap();
+        }
+        assert!(matches!(
+            image.save(&dir.join("day3-image-test.bmp")),
+            Err(ExportError::UnknownFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,272 @@
+#![feature(test)]
+extern crate test;
+
+use rayon::prelude::*;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+pub mod animation;
+pub mod bitmap;
+pub mod grid;
+pub mod image;
+pub mod render;
+pub mod search;
+pub mod slope;
+pub mod trajectory;
+
+pub use grid::{Grid, ParseGridError, ParseOptions};
+pub use slope::{Rounding, Slope};
+pub use trajectory::{par_trajectory, trajectory};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Obstacles {
+    Tree,
+    Empty,
+}
+
+impl TryFrom<char> for Obstacles {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, char> {
+        match c {
+            '#' => Ok(Obstacles::Tree),
+            '.' => Ok(Obstacles::Empty),
+            _ => Err(c),
+        }
+    }
+}
+
+impl From<Obstacles> for char {
+    fn from(cell: Obstacles) -> char {
+        match cell {
+            Obstacles::Tree => '#',
+            Obstacles::Empty => '.',
+        }
+    }
+}
+
+pub fn load_file(path: &Path) -> io::Result<String> {
+    let file = File::open(path)?;
+    let mut buf_reader = BufReader::new(file);
+    let mut contents = String::new();
+    buf_reader.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+pub fn parse_map(input: &str) -> Result<Grid<Obstacles>, ParseGridError> {
+    input.parse()
+}
+
+pub fn parse_map_with(
+    input: &str,
+    options: ParseOptions,
+) -> Result<Grid<Obstacles>, ParseGridError> {
+    Grid::parse_with(input, options)
+}
+
+#[inline(always)]
+pub fn get_trees_hit(map: &Grid<Obstacles>, run: usize, rise: usize) -> i64 {
+    trajectory(map, run, rise)
+        .filter(|&(_, _, cell)| cell == Obstacles::Tree)
+        .count() as i64
+}
+
+#[inline(always)]
+pub fn get_trees_hit_multithreaded(map: &Grid<Obstacles>, run: usize, rise: usize) -> i64 {
+    par_trajectory(map, run, rise)
+        .filter(|&(_, _, cell)| cell == Obstacles::Tree)
+        .count() as i64
+}
+
+// The slopes multiplied together for part 2.
+pub const PART2_SLOPES: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlopeCounts {
+    // Trees hit by each slope, in the order they were given.
+    pub counts: Vec<i64>,
+    // All the counts multiplied together, or `None` if that overflows.
+    pub product: Option<i64>,
+}
+
+// Trees hit by each `(run, rise)` slope, counted in one pass over the rows split up over threads.
+pub fn get_trees_hit_all(map: &Grid<Obstacles>, slopes: &[(usize, usize)]) -> SlopeCounts {
+    assert!(
+        slopes.iter().all(|&(_, rise)| rise > 0),
+        "A slope with no rise never reaches the bottom"
+    );
+    let counts = search::count_columns(map, slopes, |&(run, rise), y| {
+        if y % rise == 0 {
+            Some(y / rise * run)
+        } else {
+            None
+        }
+    });
+    let product = counts
+        .iter()
+        .try_fold(1_i64, |acc, &count| acc.checked_mul(count));
+    SlopeCounts { counts, product }
+}
+
+pub fn part1(map: &Grid<Obstacles>) -> i64 {
+    get_trees_hit_multithreaded(map, 3, 1)
+}
+
+// `None` if the product doesn't fit in an i64, which takes a map thousands of rows tall.
+pub fn part2(map: &Grid<Obstacles>) -> Option<i64> {
+    get_trees_hit_all(map, &PART2_SLOPES).product
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::Bencher;
+
+    #[test]
+    fn test_parse_map() {
+        let input = "..#\n##.".to_string();
+        let map = parse_map(&input).unwrap();
+        assert_eq!(
+            map.cells(),
+            &[
+                Obstacles::Empty,
+                Obstacles::Empty,
+                Obstacles::Tree,
+                Obstacles::Tree,
+                Obstacles::Tree,
+                Obstacles::Empty
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_map_errors() {
+        // The width used to come from the last line, so this was read as rows of 2.
+        assert_eq!(
+            parse_map("..#\n##.\n.#"),
+            Err(ParseGridError::RaggedRow {
+                line: 3,
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert!(parse_map("..#\r\n##.\r\n").is_err());
+        assert_eq!(
+            parse_map_with("..#\r\n##.\r\n", ParseOptions::lenient()),
+            parse_map("..#\n##.")
+        );
+    }
+
+    #[test]
+    fn test_get_trees_hit_multithreaded() {
+        // \n\ at end of line for nicer indentation
+        let input = "..##.......\n\
+            #...#...#..\n\
+            .#....#..#.\n\
+            ..#.#...#.#\n\
+            .#...##..#.\n\
+            ..#.##.....\n\
+            .#.#.#....#\n\
+            .#........#\n\
+            #.##...#...\n\
+            #...##....#\n\
+            .#..#...#.#";
+        let map = parse_map(input).unwrap();
+
+        let trees_hit = get_trees_hit_multithreaded(&map, 1, 1);
+        assert_eq!(trees_hit, 2);
+        let trees_hit = get_trees_hit_multithreaded(&map, 3, 1);
+        assert_eq!(trees_hit, 7);
+        let trees_hit = get_trees_hit_multithreaded(&map, 5, 1);
+        assert_eq!(trees_hit, 3);
+        let trees_hit = get_trees_hit_multithreaded(&map, 7, 1);
+        assert_eq!(trees_hit, 4);
+        let trees_hit = get_trees_hit_multithreaded(&map, 1, 2);
+        assert_eq!(trees_hit, 2);
+    }
+
+    #[test]
+    fn test_get_trees_hit_matches_multithreaded() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let map = parse_map(&input).unwrap();
+        for &(run, rise) in &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)] {
+            assert_eq!(
+                get_trees_hit(&map, run, rise),
+                get_trees_hit_multithreaded(&map, run, rise)
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_trees_hit_all() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let map = parse_map(&input).unwrap();
+        let all = get_trees_hit_all(&map, &PART2_SLOPES);
+        for (&(run, rise), &count) in PART2_SLOPES.iter().zip(&all.counts) {
+            assert_eq!(count, get_trees_hit(&map, run, rise));
+        }
+        assert_eq!(all.product, Some(all.counts.iter().product()));
+
+        // 20 slopes hitting all 11 trees is 11^20, which is too big.
+        let forest = parse_map(&["###"; 11].join("\n")).unwrap();
+        let slopes: Vec<(usize, usize)> = (0..20).map(|run| (run, 1)).collect();
+        let all = get_trees_hit_all(&forest, &slopes);
+        assert_eq!(all.counts, vec![11; 20]);
+        assert_eq!(all.product, None);
+    }
+
+    #[test]
+    fn test_get_part_2() {
+        // \n\ at end of line for nicer indentation
+        let input = "..##.......\n\
+            #...#...#..\n\
+            .#....#..#.\n\
+            ..#.#...#.#\n\
+            .#...##..#.\n\
+            ..#.##.....\n\
+            .#.#.#....#\n\
+            .#........#\n\
+            #.##...#...\n\
+            #...##....#\n\
+            .#..#...#.#";
+
+        let part2_product = part2(&parse_map(input).unwrap());
+        assert_eq!(part2_product, Some(336));
+
+        // Every slope hits a tree on every row it reaches, and 8000^4 * 4000 overflows.
+        let forest = parse_map(&["#"; 8000].join("\n")).unwrap();
+        assert_eq!(part2(&forest), None);
+    }
+
+    #[bench]
+    fn bench_parse_map(b: &mut Bencher) {
+        // \n\ at end of line for nicer indentation
+        let input = load_file(Path::new("input.txt")).unwrap();
+        b.iter(|| parse_map(&input).unwrap())
+    }
+
+    #[bench]
+    fn bench_get_trees_hit(b: &mut Bencher) {
+        // \n\ at end of line for nicer indentation
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let map = parse_map(&input).unwrap();
+        b.iter(|| get_trees_hit(&map, 3, 1))
+    }
+
+    #[bench]
+    fn bench_part2(b: &mut Bencher) {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let map = parse_map(&input).unwrap();
+        b.iter(|| part2(&map))
+    }
+
+    #[bench]
+    fn bench_get_trees_hit_multithreaded(b: &mut Bencher) {
+        // \n\ at end of line for nicer indentation
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let map = parse_map(&input).unwrap();
+        b.iter(|| get_trees_hit_multithreaded(&map, 3, 1))
+    }
+}
@@ -1,191 +1,136 @@
-#![feature(test)]
-extern crate test;
-
-use rayon::prelude::*;
-use std::fs::File;
-use std::io::{BufReader, Read, Result};
+use day3::animation::{self, AnimationOptions};
+use day3::image::{self, ImageOptions};
+use day3::render::{self, Style};
+use day3::{get_trees_hit_all, load_file, parse_map_with, part1, ParseOptions, PART2_SLOPES};
+use std::env;
+use std::io;
 use std::path::Path;
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Obstacles {
-    Tree,
-    Empty,
-}
-
-fn main() {
-    let input = load_file(Path::new("input.txt")).unwrap();
-    println!("Part 1: {:?}", part1(&input));
-    println!("Part 2: {:?}", part2(&input));
-}
-
-fn load_file(path: &Path) -> Result<String> {
-    let file = File::open(path)?;
-    let mut buf_reader = BufReader::new(file);
-    let mut contents = String::new();
-    buf_reader.read_to_string(&mut contents)?;
-    Ok(contents)
+use std::process;
+
+const USAGE: &str = "Usage: day3 [--allow-crlf] [--allow-trailing-blank-lines]
+                 [--render <run>,<rise>]... [--plain]
+                 [--image <path.ppm or path.png>] [--cell-size <pixels>]
+                 [--gif <path> [--fps <n>] [--viewport <columns>x<rows>]]";
+
+struct Options {
+    parse: ParseOptions,
+    // Slopes to draw over the map, see render.rs.
+    render: Vec<(usize, usize)>,
+    plain: bool,
+    // Where to save a picture of the map and `render` slopes, see image.rs.
+    image: Option<String>,
+    image_options: ImageOptions,
+    // Where to save an animation of the first `render` slope, or part 1's, see animation.rs.
+    gif: Option<String>,
+    animation: AnimationOptions,
 }
 
-fn parse_map(input: &str) -> (Vec<Obstacles>, usize) {
-    let mut map: Vec<Obstacles> = Vec::new();
-    let mut width = 0;
-    for line in input.lines() {
-        width = line.len();
-        for c in line.chars() {
-            map.push(match c {
-                '#' => Obstacles::Tree,
-                '.' => Obstacles::Empty,
-                _ => panic!("Expected either '#' or '.', received {:?}", c),
-            });
-        }
+fn parse_slope(slope: &str) -> Option<(usize, usize)> {
+    let (run, rise) = slope.split_once(',')?;
+    let rise = rise.parse().ok()?;
+    if rise == 0 {
+        return None;
     }
-    (map, width)
+    Some((run.parse().ok()?, rise))
 }
 
-#[inline(always)]
-fn get_trees_hit(map: &[Obstacles], run: usize, rise: usize, width: usize) -> i64 {
-    // let mut trees_hit = 0;
-    // for (i, line) in map.chunks(width).step_by(rise).enumerate() {
-    //     let x = (i * run) % width;
-    //     if line[x] == Obstacles::Tree {
-    //         trees_hit += 1;
-    //     }
-    // }
-    // trees_hit
-    map.chunks(width)
-        .step_by(rise)
-        .enumerate()
-        .fold(0_i64, |acc, (i, line)| {
-            if line[(i * run) % width] == Obstacles::Tree {
-                acc + 1
-            } else {
-                acc
+fn parse_args() -> Option<Options> {
+    let mut options = Options {
+        parse: ParseOptions::default(),
+        render: Vec::new(),
+        plain: false,
+        image: None,
+        image_options: ImageOptions::default(),
+        gif: None,
+        animation: AnimationOptions::default(),
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--allow-crlf" => options.parse.crlf = true,
+            "--allow-trailing-blank-lines" => options.parse.trailing_empty_lines = true,
+            "--plain" => options.plain = true,
+            "--render" => options.render.push(parse_slope(&args.next()?)?),
+            "--image" => options.image = Some(args.next()?),
+            "--cell-size" => options.image_options.cell_size = args.next()?.parse().ok()?,
+            "--gif" => options.gif = Some(args.next()?),
+            "--fps" => options.animation.fps = args.next()?.parse().ok().filter(|&fps| fps > 0)?,
+            "--viewport" => {
+                let viewport = args.next()?;
+                let (columns, rows) = viewport.split_once('x')?;
+                options.animation.columns = columns.parse().ok()?;
+                options.animation.rows = rows.parse().ok()?;
             }
-        })
-}
-
-#[inline(always)]
-fn get_trees_hit_multithreaded(map: &[Obstacles], run: usize, rise: usize, width: usize) -> i64 {
-    map.par_chunks(width)
-        .step_by(rise)
-        .enumerate()
-        .fold(
-            || 0_i64,
-            |acc, (i, line)| {
-                if line[(i * run) % width] == Obstacles::Tree {
-                    acc + 1
-                } else {
-                    acc
-                }
-            },
-        )
-        .sum::<i64>()
-}
-
-fn part1(input: &str) -> i64 {
-    let (map, width) = parse_map(&input);
-
-    get_trees_hit_multithreaded(&map, 3, 1, width)
-}
-
-fn part2(input: &str) -> i64 {
-    let (map, width) = parse_map(&input);
-    get_trees_hit_multithreaded(&map, 1, 1, width)
-        * get_trees_hit_multithreaded(&map, 3, 1, width)
-        * get_trees_hit_multithreaded(&map, 5, 1, width)
-        * get_trees_hit_multithreaded(&map, 7, 1, width)
-        * get_trees_hit_multithreaded(&map, 1, 2, width)
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use test::Bencher;
-
-    #[test]
-    fn test_parse_map() {
-        let input = "..#\n##.".to_string();
-        let (map, _width) = parse_map(&input);
-        assert_eq!(
-            map,
-            vec![
-                Obstacles::Empty,
-                Obstacles::Empty,
-                Obstacles::Tree,
-                Obstacles::Tree,
-                Obstacles::Tree,
-                Obstacles::Empty
-            ]
-        );
-    }
-
-    #[test]
-    fn test_get_trees_hit_multithreaded() {
-        // \n\ at end of line for nicer indentation
-        let input = "..##.......\n\
-            #...#...#..\n\
-            .#....#..#.\n\
-            ..#.#...#.#\n\
-            .#...##..#.\n\
-            ..#.##.....\n\
-            .#.#.#....#\n\
-            .#........#\n\
-            #.##...#...\n\
-            #...##....#\n\
-            .#..#...#.#";
-        let (map, width) = parse_map(&input);
-
-        let trees_hit = get_trees_hit_multithreaded(&map, 1, 1, width);
-        assert_eq!(trees_hit, 2);
-        let trees_hit = get_trees_hit_multithreaded(&map, 3, 1, width);
-        assert_eq!(trees_hit, 7);
-        let trees_hit = get_trees_hit_multithreaded(&map, 5, 1, width);
-        assert_eq!(trees_hit, 3);
-        let trees_hit = get_trees_hit_multithreaded(&map, 7, 1, width);
-        assert_eq!(trees_hit, 4);
-        let trees_hit = get_trees_hit_multithreaded(&map, 1, 2, width);
-        assert_eq!(trees_hit, 2);
+            _ => return None,
+        }
     }
+    Some(options)
+}
 
-    #[test]
-    fn test_get_part_2() {
-        // \n\ at end of line for nicer indentation
-        let input = "..##.......\n\
-            #...#...#..\n\
-            .#....#..#.\n\
-            ..#.#...#.#\n\
-            .#...##..#.\n\
-            ..#.##.....\n\
-            .#.#.#....#\n\
-            .#........#\n\
-            #.##...#...\n\
-            #...##....#\n\
-            .#..#...#.#";
+fn main() {
+    let options = parse_args().unwrap_or_else(|| {
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    });
 
-        let part2_product = part2(input);
-        assert_eq!(part2_product, 336);
+    let input = load_file(Path::new("input.txt")).unwrap();
+    let map = parse_map_with(&input, options.parse).unwrap_or_else(|err| {
+        eprintln!("Couldn't parse input.txt: {}", err);
+        process::exit(1);
+    });
+    println!("Part 1: {:?}", part1(&map));
+    let part2 = get_trees_hit_all(&map, &PART2_SLOPES);
+    match part2.product {
+        Some(product) => println!("Part 2: {:?}", product),
+        None => println!(
+            "Part 2: trees hit {:?}, but their product doesn't fit in an i64",
+            part2.counts
+        ),
     }
 
-    #[bench]
-    fn bench_parse_map(b: &mut Bencher) {
-        // \n\ at end of line for nicer indentation
-        let input = load_file(Path::new("input.txt")).unwrap();
-        b.iter(|| parse_map(&input))
+    if let Some(path) = &options.image {
+        let saved = image::draw(&map, &options.render, &options.image_options)
+            .and_then(|picture| picture.save(Path::new(path)));
+        if let Err(err) = saved {
+            eprintln!("Couldn't save {}: {}", path, err);
+            process::exit(1);
+        }
     }
 
-    #[bench]
-    fn bench_get_trees_hit(b: &mut Bencher) {
-        // \n\ at end of line for nicer indentation
-        let input = load_file(Path::new("input.txt")).unwrap();
-        let (map, width) = parse_map(&input);
-        b.iter(|| get_trees_hit(&map, 3, 1, width))
+    if let Some(path) = &options.gif {
+        let (run, rise) = options.render.first().copied().unwrap_or((3, 1));
+        let animation = AnimationOptions {
+            image: options.image_options.clone(),
+            ..options.animation.clone()
+        };
+        let actual = 100.0 / animation::frame_delay(animation.fps) as f64;
+        if actual != animation.fps as f64 {
+            eprintln!(
+                "GIF frames last whole hundredths of a second, so it'll play at {:.1} fps",
+                actual
+            );
+        }
+        if let Err(err) = animation::save_gif(Path::new(path), &map, run, rise, &animation) {
+            eprintln!("Couldn't save {}: {}", path, err);
+            process::exit(1);
+        }
     }
 
-    #[bench]
-    fn bench_get_trees_hit_multithreaded(b: &mut Bencher) {
-        // \n\ at end of line for nicer indentation
-        let input = load_file(Path::new("input.txt")).unwrap();
-        let (map, width) = parse_map(&input);
-        b.iter(|| get_trees_hit_multithreaded(&map, 3, 1, width))
+    if !options.render.is_empty() {
+        let stdout = io::stdout();
+        let style = if options.plain {
+            Style::Plain
+        } else {
+            Style::detect(&stdout)
+        };
+        println!();
+        let written = render::write_overlay(&mut stdout.lock(), &map, &options.render, style);
+        // Piping into `head` and the like closes stdout early, which is fine.
+        if let Err(err) = written {
+            if err.kind() != io::ErrorKind::BrokenPipe {
+                eprintln!("Couldn't draw the map: {}", err);
+                process::exit(1);
+            }
+        }
     }
 }
@@ -0,0 +1,215 @@
+// Drawing the map with toboggan paths on it, like the puzzle does.
+//
+// The map is repeated to the right in whole copies until every path is off the bottom. Each slope
+// gets its own marker from `marker`: the first is the puzzle's `O` for an open square and `X`
+// for a tree hit, and the rest are a lowercase letter for open and uppercase for a tree. Where
+// paths cross, the earlier slope's marker wins.
+
+use crate::trajectory::trajectory;
+use crate::{Grid, Obstacles};
+use std::io::{self, IsTerminal, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Plain,
+    // Markers in colour, with trees hit in bold.
+    Ansi,
+}
+
+impl Style {
+    // Colour if `stream` is a terminal, plain text if it's redirected.
+    pub fn detect(stream: &impl IsTerminal) -> Style {
+        if stream.is_terminal() {
+            Style::Ansi
+        } else {
+            Style::Plain
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Marker {
+    pub open: char,
+    pub tree: char,
+    // An ANSI foreground colour code.
+    pub colour: u8,
+}
+
+// Lowercase letters for the open squares of the slopes after the first, leaving out o and x so
+// they can't be mistaken for the first slope's markers.
+const LETTERS: &[u8; 24] = b"abcdefghijklmnpqrstuvwyz";
+
+// Slopes that get a marker of their own. Past that they go round the letters again.
+pub const DISTINCT_MARKERS: usize = 1 + LETTERS.len();
+
+pub fn marker(slope: usize) -> Marker {
+    // Green, cyan, magenta, yellow, blue, red, and then round again.
+    const COLOURS: [u8; 6] = [32, 36, 35, 33, 34, 31];
+    let colour = COLOURS[slope % COLOURS.len()];
+    match slope {
+        0 => Marker {
+            open: 'O',
+            tree: 'X',
+            colour,
+        },
+        _ => {
+            let letter = LETTERS[(slope - 1) % LETTERS.len()] as char;
+            Marker {
+                open: letter,
+                tree: letter.to_ascii_uppercase(),
+                colour,
+            }
+        }
+    }
+}
+
+// Which slope, if any, gets each square of the map repeated out to `columns` wide, by row. Shared
+// with image.rs so pictures match the text.
+pub(crate) struct Overlay {
+    pub columns: usize,
+    pub visits: Vec<Vec<(usize, usize)>>,
+}
+
+impl Overlay {
+    pub fn new(map: &Grid<Obstacles>, slopes: &[(usize, usize)]) -> Overlay {
+        // (x, slope) for each visited square.
+        let mut visits: Vec<Vec<(usize, usize)>> = vec![Vec::new(); map.height()];
+        let mut columns = map.width();
+        for (i, &(run, rise)) in slopes.iter().enumerate() {
+            for (x, y, _) in trajectory(map, run, rise) {
+                if !visits[y].iter().any(|&(seen, _)| seen == x) {
+                    visits[y].push((x, i));
+                }
+                columns = columns.max(x + 1);
+            }
+        }
+        Overlay {
+            columns: columns.div_ceil(map.width().max(1)) * map.width(),
+            visits,
+        }
+    }
+
+    pub fn slope_at(&self, x: usize, y: usize) -> Option<usize> {
+        self.visits[y]
+            .iter()
+            .find(|&&(seen, _)| seen == x)
+            .map(|&(_, slope)| slope)
+    }
+}
+
+pub fn write_overlay<W: Write>(
+    out: &mut W,
+    map: &Grid<Obstacles>,
+    slopes: &[(usize, usize)],
+    style: Style,
+) -> io::Result<()> {
+    let overlay = Overlay::new(map, slopes);
+    for (y, row) in map.rows().enumerate() {
+        let mut line = String::with_capacity(overlay.columns);
+        for x in 0..overlay.columns {
+            let cell = row[x % map.width()];
+            match overlay.slope_at(x, y).map(marker) {
+                None => line.push(cell.into()),
+                Some(marker) => {
+                    let (c, bold) = match cell {
+                        Obstacles::Tree => (marker.tree, ";1"),
+                        Obstacles::Empty => (marker.open, ""),
+                    };
+                    match style {
+                        Style::Plain => line.push(c),
+                        Style::Ansi => {
+                            line.push_str(&format!("\x1b[{}{}m{}\x1b[0m", marker.colour, bold, c))
+                        }
+                    }
+                }
+            }
+        }
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+pub fn render(map: &Grid<Obstacles>, slopes: &[(usize, usize)], style: Style) -> String {
+    let mut out = Vec::new();
+    write_overlay(&mut out, map, slopes, style).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_map;
+
+    const EXAMPLE: &str = "..##.......\n\
+        #...#...#..\n\
+        .#....#..#.\n\
+        ..#.#...#.#\n\
+        .#...##..#.\n\
+        ..#.##.....\n\
+        .#.#.#....#\n\
+        .#........#\n\
+        #.##...#...\n\
+        #...##....#\n\
+        .#..#...#.#";
+
+    #[test]
+    fn test_puzzle_example() {
+        let map = parse_map(EXAMPLE).unwrap();
+        // The puzzle's drawing, cut down to the three copies the path needs.
+        let expected = "O.##.........##.........##.\n\
+            #..O#...#..#...#...#..#...#\n\
+            .#....X..#..#....#..#..#...\n\
+            ..#.#...#O#..#.#...#.#..#.#\n\
+            .#...##..#..X...##..#..#...\n\
+            ..#.##.......#.X#.......#.#\n\
+            .#.#.#....#.#.#.#.O..#.#.#.\n\
+            .#........#.#........X.#...\n\
+            #.##...#...#.##...#...#.X#.\n\
+            #...##....##...##....##...#\n\
+            .#..#...#.#.#..#...#.#.#..#\n";
+        let rendered = render(&map, &[(3, 1)], Style::Plain);
+        let cut: String = rendered
+            .lines()
+            .map(|line| format!("{}\n", &line[..27]))
+            .collect();
+        assert_eq!(cut, expected);
+        assert!(rendered.lines().all(|line| line.len() == 33));
+        assert_eq!(rendered.lines().last().unwrap().chars().nth(30), Some('X'));
+    }
+
+    #[test]
+    fn test_several_slopes() {
+        let map = parse_map("...\n.#.\n..#").unwrap();
+        // The second slope gets `a`, and the third only crosses squares the others got first.
+        assert_eq!(
+            render(&map, &[(1, 1), (0, 1), (2, 2)], Style::Plain),
+            "O..\naX.\na.X\n"
+        );
+        assert_eq!(
+            render(&map, &[(4, 1)], Style::Plain),
+            "O........\n.#..X..#.\n..#..#..X\n"
+        );
+    }
+
+    #[test]
+    fn test_distinct_markers() {
+        let mut seen = std::collections::HashSet::new();
+        for slope in 0..DISTINCT_MARKERS {
+            let marker = marker(slope);
+            assert!(seen.insert(marker.open), "{:?}", marker);
+            assert!(seen.insert(marker.tree), "{:?}", marker);
+        }
+        assert!(!seen.contains(&'.') && !seen.contains(&'#'));
+        assert_eq!(marker(DISTINCT_MARKERS), marker(1));
+    }
+
+    #[test]
+    fn test_ansi() {
+        let map = parse_map(".\n#").unwrap();
+        assert_eq!(
+            render(&map, &[(0, 1)], Style::Ansi),
+            "\x1b[32mO\x1b[0m\n\x1b[32;1mX\x1b[0m\n"
+        );
+        assert_eq!(render(&map, &[(0, 1)], Style::Plain), "O\nX\n");
+    }
+}
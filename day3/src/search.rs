@@ -0,0 +1,135 @@
+// Finding the slope that hits the fewest (or most) trees.
+//
+// Every candidate slope is checked in the same sweep down the map, so each row is only read once
+// however many slopes there are, and slopes that reduce to the same fraction are only counted
+// once.
+
+use crate::slope::{Rounding, Slope};
+use crate::{Grid, Obstacles};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    FewestTrees,
+    MostTrees,
+}
+
+// Trees hit by each of `slopes`, in one pass over the rows split up over threads. `column` gives
+// the column a slope is at in row `y`, before wrapping, or `None` if it skips the row. Shared with
+// `get_trees_hit_all`.
+pub(crate) fn count_columns<S, F>(map: &Grid<Obstacles>, slopes: &[S], column: F) -> Vec<i64>
+where
+    S: Sync,
+    F: Fn(&S, usize) -> Option<usize> + Sync,
+{
+    map.par_rows()
+        .enumerate()
+        .fold(
+            || vec![0_i64; slopes.len()],
+            |mut counts, (y, row)| {
+                for (count, slope) in counts.iter_mut().zip(slopes) {
+                    if let Some(x) = column(slope, y) {
+                        if row[x % map.width()] == Obstacles::Tree {
+                            *count += 1;
+                        }
+                    }
+                }
+                counts
+            },
+        )
+        .reduce(
+            || vec![0_i64; slopes.len()],
+            |mut a, b| {
+                a.iter_mut().zip(b).for_each(|(a, b)| *a += b);
+                a
+            },
+        )
+}
+
+// Trees hit by each slope, in one pass over the rows.
+pub fn count_all(map: &Grid<Obstacles>, slopes: &[Slope], rounding: Rounding) -> Vec<i64> {
+    let width = map.width() as i64;
+    count_columns(map, slopes, |slope, y| {
+        let x = slope.x_at(y, rounding)?;
+        Some(x.rem_euclid(width) as usize)
+    })
+}
+
+// Every distinct slope with a run and rise in range, best first by `objective`. Ties go to the
+// steeper slope, then the one further left. Rises below 1 are left out.
+pub fn search(
+    map: &Grid<Obstacles>,
+    runs: RangeInclusive<i64>,
+    rises: RangeInclusive<i64>,
+    rounding: Rounding,
+    objective: Objective,
+) -> Vec<(Slope, i64)> {
+    let mut seen = HashSet::new();
+    let slopes: Vec<Slope> = rises
+        .flat_map(|rise| runs.clone().map(move |run| Slope::new(run, rise)))
+        .filter_map(Result::ok)
+        .filter(|slope| seen.insert(*slope))
+        .collect();
+
+    let counts = count_all(map, &slopes, rounding);
+    let mut ranked: Vec<(Slope, i64)> = slopes.into_iter().zip(counts).collect();
+    ranked.sort_by_key(|&(slope, hits)| {
+        let hits = match objective {
+            Objective::FewestTrees => hits,
+            Objective::MostTrees => -hits,
+        };
+        (hits, -slope.rise(), slope.run())
+    });
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_file, parse_map};
+    use std::path::Path;
+
+    const EXAMPLE: &str = "..##.......\n\
+        #...#...#..\n\
+        .#....#..#.\n\
+        ..#.#...#.#\n\
+        .#...##..#.\n\
+        ..#.##.....\n\
+        .#.#.#....#\n\
+        .#........#\n\
+        #.##...#...\n\
+        #...##....#\n\
+        .#..#...#.#";
+
+    #[test]
+    fn test_search_example() {
+        let map = parse_map(EXAMPLE).unwrap();
+        let fewest = search(&map, 1..=7, 1..=2, Rounding::Skip, Objective::FewestTrees);
+        let most = search(&map, 1..=7, 1..=2, Rounding::Skip, Objective::MostTrees);
+
+        // 2/2, 4/2 and 6/2 are the same as 1/1, 2/1 and 3/1.
+        assert_eq!(fewest.len(), 7 + 4);
+        assert_eq!(most[0], (Slope::new(3, 1).unwrap(), 7));
+        assert!(fewest.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+        assert_eq!(fewest.first().unwrap().1, most.last().unwrap().1);
+    }
+
+    #[test]
+    fn test_matches_single_slopes() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let map = parse_map(&input).unwrap();
+        for &rounding in &[Rounding::Skip, Rounding::Floor, Rounding::Nearest] {
+            for (slope, hits) in search(&map, -5..=5, 1..=4, rounding, Objective::FewestTrees) {
+                assert_eq!(hits, slope.trees_hit(&map, rounding), "{}", slope);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_ranges() {
+        let map = parse_map(EXAMPLE).unwrap();
+        assert!(search(&map, 1..=3, -2..=0, Rounding::Skip, Objective::MostTrees).is_empty());
+    }
+}
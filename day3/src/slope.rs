@@ -0,0 +1,215 @@
+// Slopes as fractions, so the toboggan can go left and move less than a column per row.
+//
+// A slope of `run/rise` moves `run` columns right (left if negative) every `rise` rows, and is
+// kept reduced so 2/4 and 1/2 are the same slope. Row `y` is at `x = y * run / rise`, which
+// isn't a whole column unless `y` is a multiple of `rise`. What happens then is up to the
+// `Rounding`:
+//
+//   Skip     only rows where x is a whole column, which is the same as the puzzle's slopes as
+//            long as run and rise have no common factor
+//   Floor    every row, at the column to the left of x
+//   Nearest  every row, at the closest column, going right on halves
+
+use crate::{Grid, Obstacles};
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+    Skip,
+    Floor,
+    Nearest,
+}
+
+// A point with a signed `x`, which is negative after going left from the start.
+pub type SignedPoint = (i64, usize, Obstacles);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Slope {
+    run: i64,
+    rise: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlopeError {
+    // The toboggan only goes downhill.
+    NonPositiveRise(i64),
+    NotAFraction(String),
+    InvalidNumber(ParseIntError),
+}
+
+impl fmt::Display for SlopeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SlopeError::NonPositiveRise(rise) => {
+                write!(f, "rise has to be at least 1, found {}", rise)
+            }
+            SlopeError::NotAFraction(s) => write!(f, "expected a slope like 3/1, found {:?}", s),
+            SlopeError::InvalidNumber(err) => write!(f, "invalid slope: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SlopeError {}
+
+impl Slope {
+    pub fn new(run: i64, rise: i64) -> Result<Slope, SlopeError> {
+        if rise <= 0 {
+            return Err(SlopeError::NonPositiveRise(rise));
+        }
+        let divisor = gcd(run.unsigned_abs(), rise as u64) as i64;
+        Ok(Slope {
+            run: run / divisor,
+            rise: rise / divisor,
+        })
+    }
+
+    pub fn run(&self) -> i64 {
+        self.run
+    }
+
+    pub fn rise(&self) -> i64 {
+        self.rise
+    }
+
+    // The column for row `y`, or `None` if it's between columns and `rounding` skips it.
+    #[inline(always)]
+    pub fn x_at(&self, y: usize, rounding: Rounding) -> Option<i64> {
+        let distance = y as i64 * self.run;
+        match rounding {
+            Rounding::Skip if distance % self.rise != 0 => None,
+            Rounding::Skip | Rounding::Floor => Some(distance.div_euclid(self.rise)),
+            Rounding::Nearest => Some((2 * distance + self.rise).div_euclid(2 * self.rise)),
+        }
+    }
+
+    pub fn trajectory<'a>(
+        &self,
+        map: &'a Grid<Obstacles>,
+        rounding: Rounding,
+    ) -> impl DoubleEndedIterator<Item = SignedPoint> + 'a {
+        let slope = *self;
+        // Skipping lands on every `rise`th row and nowhere else, since the slope is reduced.
+        let step = match rounding {
+            Rounding::Skip => self.rise as usize,
+            Rounding::Floor | Rounding::Nearest => 1,
+        };
+        (0..map.height()).step_by(step).map(move |y| {
+            let x = slope.x_at(y, rounding).unwrap();
+            (x, y, *map.get_signed(x, y).unwrap())
+        })
+    }
+
+    pub fn trees_hit(&self, map: &Grid<Obstacles>, rounding: Rounding) -> i64 {
+        self.trajectory(map, rounding)
+            .filter(|&(_, _, cell)| cell == Obstacles::Tree)
+            .count() as i64
+    }
+}
+
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.run, self.rise)
+    }
+}
+
+// Parses `run/rise`, like `3/1` or `-1/2`.
+impl FromStr for Slope {
+    type Err = SlopeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (run, rise) = s
+            .split_once('/')
+            .ok_or_else(|| SlopeError::NotAFraction(s.to_string()))?;
+        let parse = |n: &str| n.trim().parse::<i64>().map_err(SlopeError::InvalidNumber);
+        Slope::new(parse(run)?, parse(rise)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_trees_hit, load_file, parse_map};
+    use std::path::Path;
+
+    #[test]
+    fn test_reduced() {
+        assert_eq!(Slope::new(2, 4), Slope::new(1, 2));
+        assert_eq!(Slope::new(-6, 3).unwrap().to_string(), "-2/1");
+        assert_eq!(Slope::new(0, 5).unwrap().to_string(), "0/1");
+        assert_eq!(Slope::new(1, 0), Err(SlopeError::NonPositiveRise(0)));
+        assert_eq!("-3/6".parse(), Slope::new(-1, 2));
+        assert!(matches!(
+            "3".parse::<Slope>(),
+            Err(SlopeError::NotAFraction(_))
+        ));
+        assert!(matches!(
+            "a/1".parse::<Slope>(),
+            Err(SlopeError::InvalidNumber(_))
+        ));
+    }
+
+    #[test]
+    fn test_skip_matches_puzzle_slopes() {
+        let input = load_file(Path::new("input.txt")).unwrap();
+        let map = parse_map(&input).unwrap();
+        for &(run, rise) in &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)] {
+            let slope = Slope::new(run as i64, rise as i64).unwrap();
+            assert_eq!(
+                slope.trees_hit(&map, Rounding::Skip),
+                get_trees_hit(&map, run, rise)
+            );
+        }
+    }
+
+    #[test]
+    fn test_leftward_wraps() {
+        let map = parse_map("...\n..#\n.#.\n#..").unwrap();
+        let slope = Slope::new(-1, 1).unwrap();
+        assert_eq!(
+            slope.trajectory(&map, Rounding::Skip).collect::<Vec<_>>(),
+            vec![
+                (0, 0, Obstacles::Empty),
+                (-1, 1, Obstacles::Tree),
+                (-2, 2, Obstacles::Tree),
+                (-3, 3, Obstacles::Tree)
+            ]
+        );
+        assert_eq!(slope.trees_hit(&map, Rounding::Skip), 3);
+        // Going right only hits the last one, after wrapping back round to column 0.
+        assert_eq!(get_trees_hit(&map, 1, 1), 1);
+    }
+
+    #[test]
+    fn test_rounding() {
+        let slope: Slope = "1/2".parse().unwrap();
+        let xs = |rounding| (0..5).map(|y| slope.x_at(y, rounding)).collect::<Vec<_>>();
+        assert_eq!(
+            xs(Rounding::Skip),
+            vec![Some(0), None, Some(1), None, Some(2)]
+        );
+        assert_eq!(
+            xs(Rounding::Floor),
+            vec![Some(0), Some(0), Some(1), Some(1), Some(2)]
+        );
+        assert_eq!(
+            xs(Rounding::Nearest),
+            vec![Some(0), Some(1), Some(1), Some(2), Some(2)]
+        );
+
+        let slope: Slope = "-1/2".parse().unwrap();
+        assert_eq!(slope.x_at(1, Rounding::Floor), Some(-1));
+        assert_eq!(slope.x_at(1, Rounding::Nearest), Some(0));
+        assert_eq!(slope.x_at(3, Rounding::Nearest), Some(-1));
+    }
+}
@@ -0,0 +1,134 @@
+// The squares a toboggan passes through on its way down a slope.
+//
+// Points are `(x, y, cell)`, starting from the top left. `x` is how far right the toboggan has
+// travelled, so it keeps going past the width, and the cell is from column `x % width`.
+
+use crate::{Grid, Obstacles};
+use rayon::prelude::*;
+use std::iter::FusedIterator;
+
+pub type Point = (usize, usize, Obstacles);
+
+// The `i`th point along the slope, or `None` once it's past the bottom.
+#[inline(always)]
+pub fn point(map: &Grid<Obstacles>, run: usize, rise: usize, i: usize) -> Option<Point> {
+    let (x, y) = (i * run, i * rise);
+    map.get(x, y).map(|&cell| (x, y, cell))
+}
+
+// Number of points on the way down, counting the start.
+fn steps(map: &Grid<Obstacles>, rise: usize) -> usize {
+    assert!(rise > 0, "A slope with no rise never reaches the bottom");
+    map.height().div_ceil(rise)
+}
+
+#[derive(Debug, Clone)]
+pub struct Trajectory<'a> {
+    map: &'a Grid<Obstacles>,
+    run: usize,
+    rise: usize,
+    next: usize,
+    end: usize,
+}
+
+// Panics if `rise` is 0.
+pub fn trajectory(map: &Grid<Obstacles>, run: usize, rise: usize) -> Trajectory<'_> {
+    Trajectory {
+        map,
+        run,
+        rise,
+        next: 0,
+        end: steps(map, rise),
+    }
+}
+
+// The same points as `trajectory`, split up over threads.
+pub fn par_trajectory(
+    map: &Grid<Obstacles>,
+    run: usize,
+    rise: usize,
+) -> impl IndexedParallelIterator<Item = Point> + '_ {
+    (0..steps(map, rise))
+        .into_par_iter()
+        .map(move |i| point(map, run, rise, i).unwrap())
+}
+
+impl Iterator for Trajectory<'_> {
+    type Item = Point;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Point> {
+        if self.next == self.end {
+            return None;
+        }
+        let point = point(self.map, self.run, self.rise, self.next);
+        self.next += 1;
+        point
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.next;
+        (len, Some(len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Point> {
+        self.next = self.end.min(self.next.saturating_add(n));
+        self.next()
+    }
+}
+
+impl DoubleEndedIterator for Trajectory<'_> {
+    fn next_back(&mut self) -> Option<Point> {
+        if self.next == self.end {
+            return None;
+        }
+        self.end -= 1;
+        point(self.map, self.run, self.rise, self.end)
+    }
+}
+
+impl ExactSizeIterator for Trajectory<'_> {}
+
+impl FusedIterator for Trajectory<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_map;
+    use crate::Obstacles::{Empty, Tree};
+
+    #[test]
+    fn test_trajectory_points() {
+        let map = parse_map("..#\n#..\n.#.\n...").unwrap();
+        assert_eq!(
+            trajectory(&map, 2, 1).collect::<Vec<_>>(),
+            vec![(0, 0, Empty), (2, 1, Empty), (4, 2, Tree), (6, 3, Empty)]
+        );
+        assert_eq!(
+            trajectory(&map, 1, 2).collect::<Vec<_>>(),
+            vec![(0, 0, Empty), (1, 2, Tree)]
+        );
+        assert_eq!(trajectory(&map, 1, 3).len(), 2);
+        assert_eq!(trajectory(&map, 1, 4).len(), 1);
+    }
+
+    #[test]
+    fn test_iterator_adapters() {
+        let map = parse_map("..#\n#..\n.#.\n...").unwrap();
+        let mut points = trajectory(&map, 2, 1);
+        assert_eq!(points.nth(2), Some((4, 2, Tree)));
+        assert_eq!(points.next_back(), Some((6, 3, Empty)));
+        assert_eq!(points.next(), None);
+        assert_eq!(
+            trajectory(&map, 2, 1).rev().collect::<Vec<_>>(),
+            par_trajectory(&map, 2, 1).rev().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_rise() {
+        let map = parse_map("..#").unwrap();
+        trajectory(&map, 1, 0);
+    }
+}